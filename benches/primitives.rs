@@ -0,0 +1,170 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use minecraft_data_types::collections::{Prefixed, Remaining};
+use minecraft_data_types::common::{Chat, Identifier};
+use minecraft_data_types::encoder::{AsyncEncodable, Decodable};
+use minecraft_data_types::nums::{VarInt, VarLong};
+use nbt::Blob;
+use tokio::runtime::Runtime;
+use uuid::Uuid;
+
+fn runtime() -> Runtime {
+    tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap()
+}
+
+/// Benches every encode and decode path for one value, reporting throughput in wire bytes.
+fn bench_type<T: Decodable + AsyncEncodable>(
+    c: &mut Criterion,
+    group: &str,
+    name: &str,
+    value: &T,
+) {
+    let mut bytes = Vec::new();
+    value.encode(&mut bytes).unwrap();
+    let runtime = runtime();
+
+    let mut group = c.benchmark_group(group);
+    group.throughput(Throughput::Bytes(bytes.len() as u64));
+    group.bench_with_input(BenchmarkId::new("encode", name), value, |b, value| {
+        let mut out = Vec::with_capacity(bytes.len());
+        b.iter(|| {
+            out.clear();
+            value.encode(&mut out).unwrap();
+            black_box(&out);
+        })
+    });
+    group.bench_with_input(BenchmarkId::new("encode_buf", name), value, |b, value| {
+        let mut out = Vec::with_capacity(bytes.len());
+        b.iter(|| {
+            out.clear();
+            value.encode_buf(&mut out).unwrap();
+            black_box(&out);
+        })
+    });
+    group.bench_with_input(BenchmarkId::new("async_encode", name), value, |b, value| {
+        let mut out = Vec::with_capacity(bytes.len());
+        b.iter(|| {
+            out.clear();
+            runtime.block_on(value.async_encode(&mut out)).unwrap();
+            black_box(&out);
+        })
+    });
+    group.bench_with_input(BenchmarkId::new("decode", name), &bytes, |b, bytes| {
+        b.iter(|| T::decode(&mut &bytes[..]).unwrap())
+    });
+    group.bench_with_input(BenchmarkId::new("decode_buf", name), &bytes, |b, bytes| {
+        b.iter(|| T::decode_buf(&mut &bytes[..]).unwrap())
+    });
+    group.finish();
+}
+
+fn var_numbers(c: &mut Criterion) {
+    for length in 1..=5 {
+        let value = if length == 5 {
+            -1
+        } else {
+            (1 << (7 * length)) - 1
+        };
+        bench_type(
+            c,
+            "var_int",
+            &format!("{}_bytes", length),
+            &VarInt::from(value),
+        );
+    }
+    for length in 1..=10 {
+        let value = if length == 10 {
+            -1
+        } else {
+            (1i64 << (7 * length)) - 1
+        };
+        bench_type(
+            c,
+            "var_long",
+            &format!("{}_bytes", length),
+            &VarLong::from(value),
+        );
+    }
+}
+
+fn strings(c: &mut Criterion) {
+    // from a player name up to the longest string a packet may carry
+    for length in [16, 64, 256, 32767] {
+        let string: String = "minecraft:stone_".chars().cycle().take(length).collect();
+        bench_type(
+            c,
+            "identifier",
+            &length.to_string(),
+            &Identifier::from(string),
+        );
+    }
+    let chat = format!(r#"{{"text":"{}"}}"#, "a".repeat(4096));
+    bench_type(c, "chat", "4096", &Chat::from(chat));
+}
+
+fn collections(c: &mut Criterion) {
+    for length in [16, 256, 4096] {
+        let values: Vec<VarInt> = (0..length).map(VarInt::from).collect();
+        bench_type(
+            c,
+            "remaining",
+            &length.to_string(),
+            &Remaining(values.clone()),
+        );
+        bench_type(
+            c,
+            "prefixed_vec",
+            &length.to_string(),
+            &Prefixed::<VarInt, _>::new(values),
+        );
+    }
+}
+
+fn byte_arrays(c: &mut Criterion) {
+    // a message signature, then a chunk's worth of block data
+    for length in [256, 65536] {
+        bench_type(
+            c,
+            "byte_array",
+            &length.to_string(),
+            &Prefixed::<VarInt, u8>::new(vec![0x5a; length]),
+        );
+    }
+    bench_type(
+        c,
+        "long_array",
+        "256",
+        &Prefixed::<VarInt, i64>::new(vec![-1; 256]),
+    );
+}
+
+fn uuid(c: &mut Criterion) {
+    bench_type(c, "uuid", "v4", &Uuid::new_v4());
+}
+
+fn blob(c: &mut Criterion) {
+    let mut small = Blob::new();
+    small.insert("id", "minecraft:diamond_sword").unwrap();
+    small.insert("Damage", 12i32).unwrap();
+    bench_type(c, "blob", "item", &small);
+
+    let mut large = Blob::new();
+    for index in 0..256 {
+        large
+            .insert(format!("entry_{}", index), vec![index as i64; 16])
+            .unwrap();
+    }
+    bench_type(c, "blob", "256_long_arrays", &large);
+}
+
+criterion_group!(
+    benches,
+    var_numbers,
+    strings,
+    collections,
+    byte_arrays,
+    uuid,
+    blob
+);
+criterion_main!(benches);
@@ -0,0 +1,253 @@
+use bytes::Buf;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use minecraft_data_types::encoder::{AsyncEncodable, Decodable, Encodable};
+use minecraft_data_types::nums::VarInt;
+use std::io::{BufWriter, Read, Write};
+use tokio::io::AsyncWriteExt;
+
+/// Pseudo-random values of one to five bytes, weighted towards the short ids and lengths seen
+/// on the wire.
+fn values() -> Vec<i32> {
+    let mut state = 0x2545F491u32;
+    (0..1024)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            match state % 8 {
+                0..=3 => (state >> 8) as i32 & 0x7F,
+                4 | 5 => (state >> 8) as i32 & 0x3FFF,
+                6 => (state >> 4) as i32 & 0xFFFFFFF,
+                _ => -((state >> 8) as i32),
+            }
+        })
+        .collect()
+}
+
+// the byte at a time implementations these replaced
+
+fn loop_decode<R: Read>(reader: &mut R) -> anyhow::Result<i32> {
+    let mut value: i32 = 0;
+    let mut bit_offset = 0u32;
+    loop {
+        if bit_offset == 35 {
+            anyhow::bail!("Failed to decode VarInt, too many bytes.");
+        }
+        let mut buf = [0; 1];
+        reader.read_exact(&mut buf)?;
+        value |= i32::from(buf[0] & 0b01111111).overflowing_shl(bit_offset).0;
+        bit_offset += 7;
+        if buf[0] & 0b10000000 == 0 {
+            return Ok(value);
+        }
+    }
+}
+
+/// The previous `VarInt` encoding, one write per byte.
+struct LoopVarInt(i32);
+
+impl Encodable for LoopVarInt {
+    fn encode<W: Write>(&self, writer: &mut W) -> anyhow::Result<()> {
+        let mut temp = self.0 as u32;
+        loop {
+            if temp & 0xFFFFFF80 == 0 {
+                writer.write_all(&[temp as u8])?;
+                return Ok(());
+            }
+            writer.write_all(&[(temp & 0x7F | 0x80) as u8])?;
+            temp = temp.overflowing_shr(7).0;
+        }
+    }
+
+    fn size(&self) -> anyhow::Result<VarInt> {
+        let mut size = 0;
+        let mut temp = self.0 as u32;
+        loop {
+            size += 1;
+            if temp & 0xFFFFFF80 == 0 {
+                return Ok(VarInt::from(size));
+            }
+            temp = temp.overflowing_shr(7).0;
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl AsyncEncodable for LoopVarInt {
+    async fn async_encode<W: tokio::io::AsyncWrite + Send + Unpin>(
+        &self,
+        writer: &mut W,
+    ) -> anyhow::Result<()> {
+        let mut temp = self.0 as u32;
+        loop {
+            if temp & 0xFFFFFF80 == 0 {
+                writer.write_u8(temp as u8).await?;
+                return Ok(());
+            }
+            writer.write_u8((temp & 0x7F | 0x80) as u8).await?;
+            temp = temp.overflowing_shr(7).0;
+        }
+    }
+}
+
+fn decode(c: &mut Criterion) {
+    let values = values();
+    let mut bytes = Vec::new();
+    for value in &values {
+        VarInt::from(*value).encode(&mut bytes).unwrap();
+    }
+
+    let mut group = c.benchmark_group("var_int_decode");
+    group.bench_function("loop", |b| {
+        b.iter(|| {
+            let mut reader = &bytes[..];
+            for _ in 0..values.len() {
+                black_box(loop_decode(&mut reader).unwrap());
+            }
+        })
+    });
+    group.bench_function("slice", |b| {
+        b.iter(|| {
+            let mut offset = 0;
+            for _ in 0..values.len() {
+                let (value, length) = VarInt::decode_slice(&bytes[offset..]).unwrap();
+                offset += length;
+                black_box(value);
+            }
+        })
+    });
+    group.bench_function("buf", |b| {
+        b.iter(|| {
+            let mut buf = &bytes[..];
+            while buf.has_remaining() {
+                black_box(VarInt::decode_buf(&mut buf).unwrap());
+            }
+        })
+    });
+    group.finish();
+}
+
+fn encode(c: &mut Criterion) {
+    let values = values();
+    let mut group = c.benchmark_group("var_int_encode");
+    group.bench_function("loop/vec", |b| {
+        let mut bytes = Vec::with_capacity(values.len() * 5);
+        b.iter(|| {
+            bytes.clear();
+            for value in &values {
+                LoopVarInt(*value).encode(&mut bytes).unwrap();
+            }
+            black_box(&bytes);
+        })
+    });
+    group.bench_function("single_write/vec", |b| {
+        let mut bytes = Vec::with_capacity(values.len() * 5);
+        b.iter(|| {
+            bytes.clear();
+            for value in &values {
+                VarInt::from(*value).encode(&mut bytes).unwrap();
+            }
+            black_box(&bytes);
+        })
+    });
+    group.bench_function("buf/vec", |b| {
+        let mut bytes = Vec::with_capacity(values.len() * 5);
+        b.iter(|| {
+            bytes.clear();
+            for value in &values {
+                VarInt::from(*value).encode_buf(&mut bytes).unwrap();
+            }
+            black_box(&bytes);
+        })
+    });
+    group.bench_function("loop/buf_writer", |b| {
+        let mut writer = BufWriter::new(std::io::sink());
+        b.iter(|| {
+            for value in &values {
+                LoopVarInt(*value).encode(&mut writer).unwrap();
+            }
+            writer.flush().unwrap();
+        })
+    });
+    group.bench_function("single_write/buf_writer", |b| {
+        let mut writer = BufWriter::new(std::io::sink());
+        b.iter(|| {
+            for value in &values {
+                VarInt::from(*value).encode(&mut writer).unwrap();
+            }
+            writer.flush().unwrap();
+        })
+    });
+
+    group.bench_function("loop/file", |b| {
+        let mut writer = std::fs::File::create("/dev/null").unwrap();
+        b.iter(|| {
+            for value in &values {
+                LoopVarInt(*value).encode(&mut writer).unwrap();
+            }
+        })
+    });
+    group.bench_function("single_write/file", |b| {
+        let mut writer = std::fs::File::create("/dev/null").unwrap();
+        b.iter(|| {
+            for value in &values {
+                VarInt::from(*value).encode(&mut writer).unwrap();
+            }
+        })
+    });
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    group.bench_function("loop/async", |b| {
+        let mut writer = tokio::io::BufWriter::new(tokio::io::sink());
+        b.iter(|| {
+            runtime.block_on(async {
+                for value in &values {
+                    LoopVarInt(*value).async_encode(&mut writer).await.unwrap();
+                }
+                writer.flush().await.unwrap();
+            })
+        })
+    });
+    group.bench_function("single_write/async", |b| {
+        let mut writer = tokio::io::BufWriter::new(tokio::io::sink());
+        b.iter(|| {
+            runtime.block_on(async {
+                for value in &values {
+                    VarInt::from(*value)
+                        .async_encode(&mut writer)
+                        .await
+                        .unwrap();
+                }
+                writer.flush().await.unwrap();
+            })
+        })
+    });
+    group.finish();
+}
+
+fn size(c: &mut Criterion) {
+    let values = values();
+    let mut group = c.benchmark_group("var_int_size");
+    group.bench_function("loop", |b| {
+        b.iter(|| {
+            values
+                .iter()
+                .map(|value| *LoopVarInt(black_box(*value)).size().unwrap())
+                .sum::<i32>()
+        })
+    });
+    group.bench_function("leading_zeros", |b| {
+        b.iter(|| {
+            values
+                .iter()
+                .map(|value| *VarInt::from(black_box(*value)).size().unwrap())
+                .sum::<i32>()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, decode, encode, size);
+criterion_main!(benches);
@@ -0,0 +1,13 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use minecraft_data_types::encoder::{Decodable, Encodable};
+use nbt::Blob;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = Blob::decode_buf(&mut &data[..]);
+    if let Ok(blob) = Blob::decode(&mut &data[..]) {
+        let mut bytes = Vec::new();
+        blob.encode(&mut bytes).unwrap();
+        assert_eq!(*blob.size().unwrap() as usize, bytes.len());
+    }
+});
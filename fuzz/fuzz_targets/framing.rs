@@ -0,0 +1,31 @@
+#![no_main]
+use bytes::BytesMut;
+use libfuzzer_sys::fuzz_target;
+use minecraft_data_types::codec::MinecraftCodec;
+use minecraft_data_types::encoder::Decodable;
+use minecraft_data_types::packet::RawPacket;
+use tokio_util::codec::Decoder;
+
+fn drain(codec: &mut MinecraftCodec, data: &[u8]) {
+    let mut src = BytesMut::from(data);
+    while let Ok(Some(_)) = codec.decode(&mut src) {}
+}
+
+fuzz_target!(|data: &[u8]| {
+    let _ = RawPacket::decode(&mut &data[..]);
+    let _ = RawPacket::decode_buf(&mut &data[..]);
+
+    // the first byte picks the layers so one corpus covers every combination
+    let (&mode, data) = match data.split_first() {
+        Some(split) => split,
+        None => return,
+    };
+    let mut codec = MinecraftCodec::new();
+    if mode & 1 != 0 {
+        codec.set_compression_threshold(Some(usize::from(mode >> 2) * 16));
+    }
+    if mode & 2 != 0 {
+        codec.enable_encryption(&[0x2a; 16]).unwrap();
+    }
+    drain(&mut codec, data);
+});
@@ -0,0 +1,16 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use minecraft_data_types::collections::{Prefixed, PrefixedBytes, Remaining};
+use minecraft_data_types::common::Identifier;
+use minecraft_data_types::encoder::Decodable;
+use minecraft_data_types::nums::VarInt;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = Prefixed::<VarInt, VarInt>::decode(&mut &data[..]);
+    let _ = Prefixed::<VarInt, Identifier>::decode_buf(&mut &data[..]);
+    let _ = Prefixed::<i32, Identifier>::decode(&mut &data[..]);
+    let _ = Prefixed::<VarInt, Prefixed<i16, i64>>::decode_buf(&mut &data[..]);
+    let _ = Remaining::<Prefixed<u8, VarInt>>::decode(&mut &data[..]);
+    let _ = PrefixedBytes::<VarInt>::decode(&mut &data[..]);
+    let _ = PrefixedBytes::<i32>::decode_buf(&mut &data[..]);
+});
@@ -0,0 +1,18 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use minecraft_data_types::common::{Chat, Identifier};
+use minecraft_data_types::encoder::Decodable;
+use minecraft_data_types::legacy::LegacyString;
+use minecraft_data_types::packets::handshake::ServerAddress;
+
+fn decode<T: Decodable>(data: &[u8]) {
+    let _ = T::decode(&mut &data[..]);
+    let _ = T::decode_buf(&mut &data[..]);
+}
+
+fuzz_target!(|data: &[u8]| {
+    decode::<Identifier>(data);
+    decode::<Chat>(data);
+    decode::<ServerAddress>(data);
+    decode::<LegacyString>(data);
+});
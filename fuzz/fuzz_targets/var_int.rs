@@ -0,0 +1,30 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use minecraft_data_types::encoder::{Decodable, Encodable};
+use minecraft_data_types::nums::{VarInt, VarLong};
+
+fuzz_target!(|data: &[u8]| {
+    // every decoder must agree with the slice decoder on what it accepts
+    let slice = VarInt::decode_slice(data).ok();
+    let read = VarInt::decode(&mut &data[..]).ok();
+    let buf = VarInt::decode_buf(&mut &data[..]).ok();
+    assert_eq!(read, slice.map(|(value, _)| value));
+    assert_eq!(buf, read);
+    // overlong encodings are accepted, so only the value has to survive re-encoding
+    if let Some((value, _)) = slice {
+        let mut bytes = Vec::new();
+        value.encode(&mut bytes).unwrap();
+        assert_eq!(VarInt::decode_slice(&bytes).unwrap(), (value, bytes.len()));
+    }
+
+    let slice = VarLong::decode_slice(data).ok();
+    let read = VarLong::decode(&mut &data[..]).ok();
+    let buf = VarLong::decode_buf(&mut &data[..]).ok();
+    assert_eq!(read, slice.map(|(value, _)| value));
+    assert_eq!(buf, read);
+    if let Some((value, _)) = slice {
+        let mut bytes = Vec::new();
+        value.encode(&mut bytes).unwrap();
+        assert_eq!(VarLong::decode_slice(&bytes).unwrap(), (value, bytes.len()));
+    }
+});
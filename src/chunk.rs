@@ -0,0 +1,882 @@
+use crate::collections::LengthPrefix;
+use crate::encoder::*;
+use crate::limits::DecodeLimits;
+use crate::nums::VarInt;
+use bytes::Buf;
+use nbt::Blob;
+use std::io::{Read, Write};
+use std::marker::PhantomData;
+use tokio::io::AsyncWrite;
+
+/// The size and palette thresholds of a `PalettedContainer`.
+///
+/// `BlockStates` and `Biomes` match vanilla 1.20. Implement this on another type to match a
+/// registry of a different size.
+pub trait PaletteKind {
+    /// How many entries the container holds.
+    const ENTRIES: usize;
+    /// Indirect palettes never pack entries smaller than this.
+    const MIN_INDIRECT_BITS: u8;
+    /// Above this the palette is dropped and entries hold registry ids directly.
+    const MAX_INDIRECT_BITS: u8;
+    /// The bits per entry of a direct container, enough for every id in the registry. Must be
+    /// above `MAX_INDIRECT_BITS`.
+    const DIRECT_BITS: u8;
+}
+
+/// The 16x16x16 block states of a chunk section.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct BlockStates;
+
+impl PaletteKind for BlockStates {
+    const ENTRIES: usize = 4096;
+    const MIN_INDIRECT_BITS: u8 = 4;
+    const MAX_INDIRECT_BITS: u8 = 8;
+    const DIRECT_BITS: u8 = 15;
+}
+
+/// The 4x4x4 biomes of a chunk section.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct Biomes;
+
+impl PaletteKind for Biomes {
+    const ENTRIES: usize = 64;
+    const MIN_INDIRECT_BITS: u8 = 1;
+    const MAX_INDIRECT_BITS: u8 = 3;
+    const DIRECT_BITS: u8 = 6;
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Palette {
+    /// Every entry is this id and no data is sent.
+    Single(i32),
+    /// Entries are indices into these ids.
+    Indirect(Vec<i32>),
+    /// Entries are the ids themselves.
+    Direct,
+}
+
+/// A fixed number of registry ids, packed into longs behind a palette.
+///
+/// Uses the 1.16+ packing: entries never span two longs, the leftover high bits of each long are
+/// zero. The long array is prefixed with its length, as it is up to 1.20.4.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct PalettedContainer<K> {
+    bits: u8,
+    palette: Palette,
+    data: Vec<i64>,
+    kind: PhantomData<K>,
+}
+
+/// The fewest bits that can index `count` distinct entries.
+fn bits_for(count: usize) -> u8 {
+    (usize::BITS - count.saturating_sub(1).leading_zeros()) as u8
+}
+
+impl<K: PaletteKind> PalettedContainer<K> {
+    /// A container with every entry set to `value`.
+    pub fn single(value: i32) -> Self {
+        PalettedContainer {
+            bits: 0,
+            palette: Palette::Single(value),
+            data: Vec::new(),
+            kind: PhantomData,
+        }
+    }
+
+    pub fn bits_per_entry(&self) -> u8 {
+        self.bits
+    }
+
+    pub fn palette(&self) -> &Palette {
+        &self.palette
+    }
+
+    /// The packed entries, empty for a single valued container.
+    pub fn data(&self) -> &[i64] {
+        &self.data
+    }
+
+    pub fn get(&self, index: usize) -> Option<i32> {
+        if index >= K::ENTRIES {
+            return None;
+        }
+        match &self.palette {
+            Palette::Single(value) => Some(*value),
+            Palette::Indirect(palette) => palette.get(self.raw(index) as usize).copied(),
+            Palette::Direct => Some(self.raw(index) as i32),
+        }
+    }
+
+    /// Sets an entry, growing the palette and repacking the data when `value` doesn't fit.
+    pub fn set(&mut self, index: usize, value: i32) -> anyhow::Result<()> {
+        if index >= K::ENTRIES {
+            anyhow::bail!(
+                "Failed to set entry {} of a paletted container with {} entries.",
+                index,
+                K::ENTRIES
+            );
+        }
+        if value < 0 || bits_for(value as usize + 1) > K::DIRECT_BITS {
+            anyhow::bail!(
+                "Failed to store id {} in a paletted container with {} bit ids.",
+                value,
+                K::DIRECT_BITS
+            );
+        }
+
+        let raw = match &mut self.palette {
+            Palette::Single(single) if *single == value => return Ok(()),
+            Palette::Single(single) => {
+                let palette = vec![*single, value];
+                self.repack(Palette::Indirect(palette), K::MIN_INDIRECT_BITS.max(1));
+                1
+            }
+            Palette::Indirect(palette) => match palette.iter().position(|id| *id == value) {
+                Some(position) => position,
+                None => {
+                    palette.push(value);
+                    let length = palette.len();
+                    if length > 1 << self.bits {
+                        let bits = K::MIN_INDIRECT_BITS.max(bits_for(length));
+                        if bits > K::MAX_INDIRECT_BITS {
+                            self.repack(Palette::Direct, K::DIRECT_BITS);
+                            value as usize
+                        } else {
+                            let palette = palette.clone();
+                            self.repack(Palette::Indirect(palette), bits);
+                            length - 1
+                        }
+                    } else {
+                        length - 1
+                    }
+                }
+            },
+            Palette::Direct => value as usize,
+        };
+        self.set_raw(index, raw as u64);
+        Ok(())
+    }
+
+    fn entries_per_long(bits: u8) -> usize {
+        64 / bits as usize
+    }
+
+    fn longs_for(bits: u8) -> usize {
+        if bits == 0 {
+            0
+        } else {
+            K::ENTRIES.div_ceil(Self::entries_per_long(bits))
+        }
+    }
+
+    fn raw(&self, index: usize) -> u64 {
+        let per_long = Self::entries_per_long(self.bits);
+        let shift = (index % per_long) * self.bits as usize;
+        (self.data[index / per_long] as u64 >> shift) & ((1 << self.bits) - 1)
+    }
+
+    fn set_raw(&mut self, index: usize, raw: u64) {
+        let per_long = Self::entries_per_long(self.bits);
+        let shift = (index % per_long) * self.bits as usize;
+        let long = &mut self.data[index / per_long];
+        let mask = ((1u64 << self.bits) - 1) << shift;
+        *long = ((*long as u64 & !mask) | (raw << shift)) as i64;
+    }
+
+    /// Rewrites every entry under a new palette and entry size. An indirect `palette` must hold
+    /// every id already present.
+    fn repack(&mut self, palette: Palette, bits: u8) {
+        let ids: Vec<i32> = (0..K::ENTRIES)
+            .map(|index| self.get(index).unwrap_or_default())
+            .collect();
+        self.palette = palette;
+        self.bits = bits;
+        self.data = vec![0; Self::longs_for(bits)];
+        for (index, id) in ids.into_iter().enumerate() {
+            let raw = match &self.palette {
+                Palette::Indirect(palette) => palette
+                    .iter()
+                    .position(|entry| *entry == id)
+                    .unwrap_or_default(),
+                _ => id as usize,
+            };
+            self.set_raw(index, raw as u64);
+        }
+    }
+
+    /// The entry size actually used for a sent bits per entry, as the vanilla client picks it.
+    fn decoded_bits(bits: u8) -> u8 {
+        if bits == 0 {
+            0
+        } else if bits <= K::MAX_INDIRECT_BITS {
+            bits.max(K::MIN_INDIRECT_BITS)
+        } else {
+            K::DIRECT_BITS
+        }
+    }
+
+    fn decoded_length(bits: u8, length: VarInt) -> anyhow::Result<usize> {
+        let length = length.to_length()?;
+        if length != Self::longs_for(bits) {
+            anyhow::bail!(
+                "Malformed paletted container found. {} longs for {} bits per entry",
+                length,
+                bits
+            );
+        }
+        Ok(length)
+    }
+
+    fn from_parts(bits: u8, palette: Palette, data: Vec<i64>) -> anyhow::Result<Self> {
+        let container = PalettedContainer {
+            bits,
+            palette,
+            data,
+            kind: PhantomData,
+        };
+        if let Palette::Indirect(palette) = &container.palette {
+            if let Some(index) =
+                (0..K::ENTRIES).find(|index| container.raw(*index) as usize >= palette.len())
+            {
+                anyhow::bail!(
+                    "Malformed paletted container found. Entry {} is outside a palette of {}",
+                    index,
+                    palette.len()
+                );
+            }
+        }
+        Ok(container)
+    }
+}
+
+impl<K: PaletteKind> Decodable for PalettedContainer<K> {
+    fn decode<R: Read>(reader: &mut R) -> anyhow::Result<Self> {
+        Self::decode_limited(reader, &mut DecodeLimits::default())
+    }
+
+    fn decode_buf<B: bytes::Buf>(buf: &mut B) -> anyhow::Result<Self> {
+        Self::decode_buf_limited(buf, &mut DecodeLimits::default())
+    }
+
+    fn decode_buf_limited<B: bytes::Buf>(
+        buf: &mut B,
+        limits: &mut DecodeLimits,
+    ) -> anyhow::Result<Self> {
+        let bits = Self::decoded_bits(u8::decode_buf(buf)?);
+        let palette = if bits == 0 {
+            Palette::Single(*VarInt::decode_buf(buf)?)
+        } else if bits <= K::MAX_INDIRECT_BITS {
+            let length = VarInt::decode_buf(buf)?.to_length()?;
+            let ids = VarInt::decode_many_buf_limited(buf, length, limits)?;
+            Palette::Indirect(ids.into_iter().map(i32::from).collect())
+        } else {
+            Palette::Direct
+        };
+        let length = Self::decoded_length(bits, VarInt::decode_buf(buf)?)?;
+        Self::from_parts(
+            bits,
+            palette,
+            i64::decode_many_buf_limited(buf, length, limits)?,
+        )
+    }
+
+    fn decode_limited<R: Read>(reader: &mut R, limits: &mut DecodeLimits) -> anyhow::Result<Self> {
+        let bits = Self::decoded_bits(u8::decode(reader)?);
+        let palette = if bits == 0 {
+            Palette::Single(*VarInt::decode(reader)?)
+        } else if bits <= K::MAX_INDIRECT_BITS {
+            let length = VarInt::decode(reader)?.to_length()?;
+            let ids = VarInt::decode_many(reader, length, limits)?;
+            Palette::Indirect(ids.into_iter().map(i32::from).collect())
+        } else {
+            Palette::Direct
+        };
+        let length = Self::decoded_length(bits, VarInt::decode(reader)?)?;
+        Self::from_parts(bits, palette, i64::decode_many(reader, length, limits)?)
+    }
+}
+
+impl<K: PaletteKind> Encodable for PalettedContainer<K> {
+    fn encode<W: Write>(&self, writer: &mut W) -> anyhow::Result<()> {
+        self.bits.encode(writer)?;
+        match &self.palette {
+            Palette::Single(value) => VarInt::from(*value).encode(writer)?,
+            Palette::Indirect(palette) => {
+                VarInt::from_length(palette.len())?.encode(writer)?;
+                for id in palette {
+                    VarInt::from(*id).encode(writer)?;
+                }
+            }
+            Palette::Direct => (),
+        }
+        VarInt::from_length(self.data.len())?.encode(writer)?;
+        i64::encode_many(&self.data, writer)
+    }
+
+    fn size(&self) -> anyhow::Result<VarInt> {
+        let mut size = VarInt::from(1);
+        match &self.palette {
+            Palette::Single(value) => size = size.try_add(VarInt::from(*value).size()?)?,
+            Palette::Indirect(palette) => {
+                size = size.try_add(VarInt::from_length(palette.len())?.size()?)?;
+                for id in palette {
+                    size = size.try_add(VarInt::from(*id).size()?)?;
+                }
+            }
+            Palette::Direct => (),
+        }
+        size.try_add(VarInt::from_length(self.data.len())?.size()?)?
+            .try_add(i64::size_many(&self.data)?)
+    }
+
+    fn encode_buf<B: bytes::BufMut>(&self, buf: &mut B) -> anyhow::Result<()> {
+        self.bits.encode_buf(buf)?;
+        match &self.palette {
+            Palette::Single(value) => VarInt::from(*value).encode_buf(buf)?,
+            Palette::Indirect(palette) => {
+                VarInt::from_length(palette.len())?.encode_buf(buf)?;
+                for id in palette {
+                    VarInt::from(*id).encode_buf(buf)?;
+                }
+            }
+            Palette::Direct => (),
+        }
+        VarInt::from_length(self.data.len())?.encode_buf(buf)?;
+        i64::encode_many_buf(&self.data, buf)
+    }
+}
+
+#[async_trait::async_trait]
+impl<K: PaletteKind + Send + Sync> AsyncEncodable for PalettedContainer<K> {
+    async fn async_encode<W: AsyncWrite + Send + Unpin>(
+        &self,
+        writer: &mut W,
+    ) -> anyhow::Result<()> {
+        self.bits.async_encode(writer).await?;
+        match &self.palette {
+            Palette::Single(value) => VarInt::from(*value).async_encode(writer).await?,
+            Palette::Indirect(palette) => {
+                VarInt::from_length(palette.len())?
+                    .async_encode(writer)
+                    .await?;
+                for id in palette {
+                    VarInt::from(*id).async_encode(writer).await?;
+                }
+            }
+            Palette::Direct => (),
+        }
+        VarInt::from_length(self.data.len())?
+            .async_encode(writer)
+            .await?;
+        i64::async_encode_many(&self.data, writer).await
+    }
+}
+
+/// One 16 block tall slice of a chunk column.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct ChunkSection {
+    /// Blocks other than air, which the client uses to skip empty sections.
+    pub block_count: i16,
+    pub blocks: PalettedContainer<BlockStates>,
+    pub biomes: PalettedContainer<Biomes>,
+}
+
+fn block_index(x: i32, y: i32, z: i32) -> usize {
+    (((y & 15) << 8) | ((z & 15) << 4) | (x & 15)) as usize
+}
+
+fn biome_index(x: i32, y: i32, z: i32) -> usize {
+    ((((y & 15) >> 2) << 4) | (((z & 15) >> 2) << 2) | ((x & 15) >> 2)) as usize
+}
+
+impl ChunkSection {
+    /// A section of only air in a single biome.
+    pub fn empty(biome: i32) -> Self {
+        ChunkSection {
+            block_count: 0,
+            blocks: PalettedContainer::single(0),
+            biomes: PalettedContainer::single(biome),
+        }
+    }
+
+    /// Sets a block, keeping `block_count` up to date. Only state 0 counts as air, so the
+    /// count is off when other air states such as cave air are placed.
+    pub fn set_block(&mut self, index: usize, state: i32) -> anyhow::Result<()> {
+        let previous = self.blocks.get(index).unwrap_or_default();
+        self.blocks.set(index, state)?;
+        match (previous == 0, state == 0) {
+            (true, false) => self.block_count += 1,
+            (false, true) => self.block_count -= 1,
+            _ => (),
+        }
+        Ok(())
+    }
+}
+
+impl Decodable for ChunkSection {
+    fn decode<R: Read>(reader: &mut R) -> anyhow::Result<Self> {
+        Self::decode_limited(reader, &mut DecodeLimits::default())
+    }
+
+    fn decode_buf<B: bytes::Buf>(buf: &mut B) -> anyhow::Result<Self> {
+        Self::decode_buf_limited(buf, &mut DecodeLimits::default())
+    }
+
+    fn decode_buf_limited<B: bytes::Buf>(
+        buf: &mut B,
+        limits: &mut DecodeLimits,
+    ) -> anyhow::Result<Self> {
+        Ok(ChunkSection {
+            block_count: i16::decode_buf(buf)?,
+            blocks: PalettedContainer::decode_buf_limited(buf, limits)?,
+            biomes: PalettedContainer::decode_buf_limited(buf, limits)?,
+        })
+    }
+
+    fn decode_limited<R: Read>(reader: &mut R, limits: &mut DecodeLimits) -> anyhow::Result<Self> {
+        Ok(ChunkSection {
+            block_count: i16::decode(reader)?,
+            blocks: PalettedContainer::decode_limited(reader, limits)?,
+            biomes: PalettedContainer::decode_limited(reader, limits)?,
+        })
+    }
+}
+
+impl Encodable for ChunkSection {
+    fn encode<W: Write>(&self, writer: &mut W) -> anyhow::Result<()> {
+        self.block_count.encode(writer)?;
+        self.blocks.encode(writer)?;
+        self.biomes.encode(writer)
+    }
+
+    fn size(&self) -> anyhow::Result<VarInt> {
+        self.block_count
+            .size()?
+            .try_add(self.blocks.size()?)?
+            .try_add(self.biomes.size()?)
+    }
+
+    fn encode_buf<B: bytes::BufMut>(&self, buf: &mut B) -> anyhow::Result<()> {
+        self.block_count.encode_buf(buf)?;
+        self.blocks.encode_buf(buf)?;
+        self.biomes.encode_buf(buf)
+    }
+}
+
+#[async_trait::async_trait]
+impl AsyncEncodable for ChunkSection {
+    async fn async_encode<W: AsyncWrite + Send + Unpin>(
+        &self,
+        writer: &mut W,
+    ) -> anyhow::Result<()> {
+        self.block_count.async_encode(writer).await?;
+        self.blocks.async_encode(writer).await?;
+        self.biomes.async_encode(writer).await
+    }
+}
+
+/// The vertical extent of a dimension, which decides how many sections a column sends.
+///
+/// The dimension type comes from the registry rather than the packet, so build this from the
+/// `min_y` and `height` of the dimension the client was sent into.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct WorldHeight {
+    min_y: i32,
+    height: i32,
+}
+
+impl WorldHeight {
+    /// The vanilla overworld, from y -64 up to 319.
+    pub const OVERWORLD: WorldHeight = WorldHeight {
+        min_y: -64,
+        height: 384,
+    };
+    /// The vanilla nether and end, from y 0 up to 255.
+    pub const CLASSIC: WorldHeight = WorldHeight {
+        min_y: 0,
+        height: 256,
+    };
+
+    /// Checks the bounds vanilla puts on dimension types: both multiples of 16, and the world
+    /// within y -2032 to 2031.
+    pub fn new(min_y: i32, height: i32) -> anyhow::Result<Self> {
+        if min_y % 16 != 0
+            || height % 16 != 0
+            || height < 16
+            || min_y < -2032
+            || height > 4064
+            || min_y + height > 2032
+        {
+            anyhow::bail!(
+                "Malformed world height found. Min y {}, height {}",
+                min_y,
+                height
+            );
+        }
+        Ok(WorldHeight { min_y, height })
+    }
+
+    pub fn min_y(&self) -> i32 {
+        self.min_y
+    }
+
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+
+    /// How many sections a column of this height holds.
+    pub fn sections(&self) -> usize {
+        (self.height / 16) as usize
+    }
+}
+
+/// A block entity sent with its chunk.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BlockEntity {
+    /// The x within the chunk, 0 to 15.
+    pub x: u8,
+    /// The z within the chunk, 0 to 15.
+    pub z: u8,
+    pub y: i16,
+    /// The block entity type registry id.
+    pub kind: VarInt,
+    /// Left out when the block entity has nothing to sync.
+    pub data: Option<Blob>,
+}
+
+impl BlockEntity {
+    fn packed_xz(&self) -> anyhow::Result<u8> {
+        if self.x > 15 || self.z > 15 {
+            anyhow::bail!(
+                "Failed to pack block entity position {}, {} within a chunk.",
+                self.x,
+                self.z
+            );
+        }
+        Ok(self.x << 4 | self.z)
+    }
+
+    fn from_parts(packed_xz: u8, y: i16, kind: VarInt, data: Option<Blob>) -> Self {
+        BlockEntity {
+            x: packed_xz >> 4,
+            z: packed_xz & 15,
+            y,
+            kind,
+            data,
+        }
+    }
+}
+
+impl Decodable for BlockEntity {
+    fn decode<R: Read>(reader: &mut R) -> anyhow::Result<Self> {
+        Self::decode_limited(reader, &mut DecodeLimits::default())
+    }
+
+    fn decode_buf<B: bytes::Buf>(buf: &mut B) -> anyhow::Result<Self> {
+        Self::decode_buf_limited(buf, &mut DecodeLimits::default())
+    }
+
+    fn decode_buf_limited<B: bytes::Buf>(
+        buf: &mut B,
+        limits: &mut DecodeLimits,
+    ) -> anyhow::Result<Self> {
+        let packed_xz = u8::decode_buf(buf)?;
+        let y = i16::decode_buf(buf)?;
+        let kind = VarInt::decode_buf(buf)?;
+        check_remaining(buf, 1, "block entity")?;
+        let data = if buf.chunk()[0] == 0x00 {
+            buf.advance(1);
+            None
+        } else {
+            Some(Blob::decode_buf_limited(buf, limits)?)
+        };
+        Ok(BlockEntity::from_parts(packed_xz, y, kind, data))
+    }
+
+    fn decode_limited<R: Read>(reader: &mut R, limits: &mut DecodeLimits) -> anyhow::Result<Self> {
+        let packed_xz = u8::decode(reader)?;
+        let y = i16::decode(reader)?;
+        let kind = VarInt::decode(reader)?;
+        // an empty tag is sent as a lone end tag
+        let data = match u8::decode(reader)? {
+            0x00 => None,
+            tag => Some(Blob::decode_limited(
+                &mut Read::chain(&[tag][..], reader),
+                limits,
+            )?),
+        };
+        Ok(BlockEntity::from_parts(packed_xz, y, kind, data))
+    }
+}
+
+impl Encodable for BlockEntity {
+    fn encode<W: Write>(&self, writer: &mut W) -> anyhow::Result<()> {
+        self.packed_xz()?.encode(writer)?;
+        self.y.encode(writer)?;
+        self.kind.encode(writer)?;
+        match &self.data {
+            Some(data) => data.encode(writer),
+            None => 0u8.encode(writer),
+        }
+    }
+
+    fn size(&self) -> anyhow::Result<VarInt> {
+        let data = match &self.data {
+            Some(data) => data.size()?,
+            None => VarInt::from(1),
+        };
+        VarInt::from(3).try_add(self.kind.size()?)?.try_add(data)
+    }
+}
+
+#[async_trait::async_trait]
+impl AsyncEncodable for BlockEntity {
+    async fn async_encode<W: AsyncWrite + Send + Unpin>(
+        &self,
+        writer: &mut W,
+    ) -> anyhow::Result<()> {
+        self.packed_xz()?.async_encode(writer).await?;
+        self.y.async_encode(writer).await?;
+        self.kind.async_encode(writer).await?;
+        match &self.data {
+            Some(data) => data.async_encode(writer).await,
+            None => 0u8.async_encode(writer).await,
+        }
+    }
+}
+
+/// The chunk half of Chunk Data and Update Light, as sent by 1.20 to 1.20.1: everything up to
+/// the light arrays.
+///
+/// `sections` run from the bottom of the world up, `height.sections()` of them. The packet
+/// doesn't say how many there are, so columns decode with the height of their dimension rather
+/// than through `Decodable`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChunkColumn {
+    pub x: i32,
+    pub z: i32,
+    pub heightmaps: Blob,
+    pub sections: Vec<ChunkSection>,
+    pub block_entities: Vec<BlockEntity>,
+    height: WorldHeight,
+}
+
+impl ChunkColumn {
+    /// A column of empty sections in a single biome.
+    pub fn empty(height: WorldHeight, x: i32, z: i32, biome: i32) -> Self {
+        Self::new(
+            height,
+            x,
+            z,
+            Blob::new(),
+            vec![ChunkSection::empty(biome); height.sections()],
+            Vec::new(),
+        )
+    }
+
+    pub fn new(
+        height: WorldHeight,
+        x: i32,
+        z: i32,
+        heightmaps: Blob,
+        sections: Vec<ChunkSection>,
+        block_entities: Vec<BlockEntity>,
+    ) -> Self {
+        ChunkColumn {
+            x,
+            z,
+            heightmaps,
+            sections,
+            block_entities,
+            height,
+        }
+    }
+
+    pub fn height(&self) -> WorldHeight {
+        self.height
+    }
+
+    /// The section holding a world position, if it is within this column.
+    fn section_at(&self, x: i32, y: i32, z: i32) -> Option<usize> {
+        let min_y = self.height.min_y;
+        if x >> 4 != self.x || z >> 4 != self.z || y < min_y {
+            return None;
+        }
+        let section = (y.checked_sub(min_y)? >> 4) as usize;
+        if section < self.sections.len() {
+            Some(section)
+        } else {
+            None
+        }
+    }
+
+    fn section_at_mut(&mut self, x: i32, y: i32, z: i32) -> anyhow::Result<&mut ChunkSection> {
+        match self.section_at(x, y, z) {
+            Some(section) => Ok(&mut self.sections[section]),
+            None => anyhow::bail!(
+                "Failed to find {}, {}, {} in chunk {}, {}.",
+                x,
+                y,
+                z,
+                self.x,
+                self.z
+            ),
+        }
+    }
+
+    pub fn block(&self, x: i32, y: i32, z: i32) -> Option<i32> {
+        let section = self.section_at(x, y, z)?;
+        self.sections[section].blocks.get(block_index(x, y, z))
+    }
+
+    pub fn set_block(&mut self, x: i32, y: i32, z: i32, state: i32) -> anyhow::Result<()> {
+        self.section_at_mut(x, y, z)?
+            .set_block(block_index(x, y, z), state)
+    }
+
+    pub fn biome(&self, x: i32, y: i32, z: i32) -> Option<i32> {
+        let section = self.section_at(x, y, z)?;
+        self.sections[section].biomes.get(biome_index(x, y, z))
+    }
+
+    pub fn set_biome(&mut self, x: i32, y: i32, z: i32, biome: i32) -> anyhow::Result<()> {
+        self.section_at_mut(x, y, z)?
+            .biomes
+            .set(biome_index(x, y, z), biome)
+    }
+
+    pub fn block_entity(&self, x: i32, y: i32, z: i32) -> Option<&BlockEntity> {
+        self.section_at(x, y, z)?;
+        self.block_entities.iter().find(|entity| {
+            i32::from(entity.x) == x & 15
+                && i32::from(entity.z) == z & 15
+                && i32::from(entity.y) == y
+        })
+    }
+
+    fn check_sections(&self) -> anyhow::Result<()> {
+        if self.sections.len() != self.height.sections() {
+            anyhow::bail!(
+                "Failed to encode chunk with {} sections, the world has {}.",
+                self.sections.len(),
+                self.height.sections()
+            );
+        }
+        Ok(())
+    }
+
+    fn sections_size(&self) -> anyhow::Result<VarInt> {
+        self.check_sections()?;
+        ChunkSection::size_many(&self.sections)
+    }
+
+    fn leftover_error(leftover: u64, height: WorldHeight) -> anyhow::Error {
+        anyhow::anyhow!(
+            "Malformed chunk data found. {} bytes left after {} sections",
+            leftover,
+            height.sections()
+        )
+    }
+
+    pub fn decode<R: Read>(reader: &mut R, height: WorldHeight) -> anyhow::Result<Self> {
+        Self::decode_limited(reader, height, &mut DecodeLimits::default())
+    }
+
+    pub fn decode_buf<B: bytes::Buf>(buf: &mut B, height: WorldHeight) -> anyhow::Result<Self> {
+        Self::decode_buf_limited(buf, height, &mut DecodeLimits::default())
+    }
+
+    pub fn decode_buf_limited<B: bytes::Buf>(
+        buf: &mut B,
+        height: WorldHeight,
+        limits: &mut DecodeLimits,
+    ) -> anyhow::Result<Self> {
+        let x = i32::decode_buf(buf)?;
+        let z = i32::decode_buf(buf)?;
+        let heightmaps = Blob::decode_buf_limited(buf, limits)?;
+        let size = VarInt::decode_buf(buf)?.to_length()?;
+        check_remaining(buf, size, "chunk data")?;
+        let mut data = buf.copy_to_bytes(size);
+        let sections = ChunkSection::decode_many_buf_limited(&mut data, height.sections(), limits)?;
+        if data.has_remaining() {
+            return Err(Self::leftover_error(data.remaining() as u64, height));
+        }
+        let length = VarInt::decode_buf(buf)?.to_length()?;
+        let block_entities = BlockEntity::decode_many_buf_limited(buf, length, limits)?;
+        Ok(Self::new(
+            height,
+            x,
+            z,
+            heightmaps,
+            sections,
+            block_entities,
+        ))
+    }
+
+    pub fn decode_limited<R: Read>(
+        reader: &mut R,
+        height: WorldHeight,
+        limits: &mut DecodeLimits,
+    ) -> anyhow::Result<Self> {
+        let x = i32::decode(reader)?;
+        let z = i32::decode(reader)?;
+        let heightmaps = Blob::decode_limited(reader, limits)?;
+        let size = VarInt::decode(reader)?.to_length()?;
+        let mut data = reader.take(size as u64);
+        let sections = ChunkSection::decode_many(&mut data, height.sections(), limits)?;
+        if data.limit() != 0 {
+            return Err(Self::leftover_error(data.limit(), height));
+        }
+        let length = VarInt::decode(reader)?.to_length()?;
+        let block_entities = BlockEntity::decode_many(reader, length, limits)?;
+        Ok(Self::new(
+            height,
+            x,
+            z,
+            heightmaps,
+            sections,
+            block_entities,
+        ))
+    }
+}
+
+impl Encodable for ChunkColumn {
+    fn encode<W: Write>(&self, writer: &mut W) -> anyhow::Result<()> {
+        self.x.encode(writer)?;
+        self.z.encode(writer)?;
+        self.heightmaps.encode(writer)?;
+        self.sections_size()?.encode(writer)?;
+        ChunkSection::encode_many(&self.sections, writer)?;
+        VarInt::from_length(self.block_entities.len())?.encode(writer)?;
+        BlockEntity::encode_many(&self.block_entities, writer)
+    }
+
+    fn size(&self) -> anyhow::Result<VarInt> {
+        let sections = self.sections_size()?;
+        VarInt::from(8)
+            .try_add(self.heightmaps.size()?)?
+            .try_add(sections.size()?)?
+            .try_add(sections)?
+            .try_add(VarInt::from_length(self.block_entities.len())?.size()?)?
+            .try_add(BlockEntity::size_many(&self.block_entities)?)
+    }
+}
+
+#[async_trait::async_trait]
+impl AsyncEncodable for ChunkColumn {
+    async fn async_encode<W: AsyncWrite + Send + Unpin>(
+        &self,
+        writer: &mut W,
+    ) -> anyhow::Result<()> {
+        self.x.async_encode(writer).await?;
+        self.z.async_encode(writer).await?;
+        self.heightmaps.async_encode(writer).await?;
+        self.sections_size()?.async_encode(writer).await?;
+        ChunkSection::async_encode_many(&self.sections, writer).await?;
+        VarInt::from_length(self.block_entities.len())?
+            .async_encode(writer)
+            .await?;
+        BlockEntity::async_encode_many(&self.block_entities, writer).await
+    }
+}
@@ -0,0 +1,298 @@
+use crate::encoder::Encodable;
+use crate::limits::DecodeLimits;
+use crate::nums::VarInt;
+use crate::packet::{RawPacket, MAX_PACKET_LENGTH};
+use crate::registry::{DecodedPacket, PacketRegistry};
+use crate::state::{ConnectionState, ConnectionStateMachine, Direction};
+use anyhow::Context;
+use bytes::{Buf, BufMut, BytesMut};
+use cfb8::cipher::generic_array::GenericArray;
+use cfb8::cipher::{BlockDecryptMut, BlockEncryptMut, KeyIvInit};
+use std::convert::TryFrom;
+use std::io::{Read, Write};
+use std::sync::Arc;
+use tokio_util::codec::{Decoder, Encoder};
+
+/// The largest uncompressed body a vanilla server accepts in a compressed frame.
+pub const MAX_UNCOMPRESSED_LENGTH: usize = 8388608;
+
+type Aes128Cfb8Encryptor = cfb8::Encryptor<aes::Aes128>;
+type Aes128Cfb8Decryptor = cfb8::Decryptor<aes::Aes128>;
+
+/// Frames `RawPacket`s for `tokio_util::codec::Framed`.
+///
+/// Compression and encryption start out disabled and are switched on at runtime, after Set
+/// Compression and Encryption Response respectively.
+#[derive(Default)]
+pub struct MinecraftCodec {
+    compression_threshold: Option<usize>,
+    encryptor: Option<Aes128Cfb8Encryptor>,
+    decryptor: Option<Aes128Cfb8Decryptor>,
+    decrypted: usize,
+}
+
+impl MinecraftCodec {
+    pub fn new() -> Self {
+        MinecraftCodec::default()
+    }
+
+    pub fn compression_threshold(&self) -> Option<usize> {
+        self.compression_threshold
+    }
+
+    /// `None` disables compression, which is the state before Set Compression.
+    pub fn set_compression_threshold(&mut self, threshold: Option<usize>) {
+        self.compression_threshold = threshold;
+    }
+
+    pub fn is_encrypted(&self) -> bool {
+        self.encryptor.is_some()
+    }
+
+    /// Enables AES/CFB8 in both directions with the shared secret as both key and IV. Bytes
+    /// already buffered but not yet decoded are treated as ciphertext.
+    pub fn enable_encryption(&mut self, shared_secret: &[u8]) -> anyhow::Result<()> {
+        self.encryptor = Some(
+            Aes128Cfb8Encryptor::new_from_slices(shared_secret, shared_secret)
+                .map_err(|_| anyhow::anyhow!("Shared secret must be 16 bytes."))?,
+        );
+        self.decryptor = Some(
+            Aes128Cfb8Decryptor::new_from_slices(shared_secret, shared_secret)
+                .map_err(|_| anyhow::anyhow!("Shared secret must be 16 bytes."))?,
+        );
+        self.decrypted = 0;
+        Ok(())
+    }
+
+    fn decode_body(&self, mut frame: BytesMut) -> anyhow::Result<RawPacket> {
+        let threshold = match self.compression_threshold {
+            Some(threshold) => threshold,
+            None => return RawPacket::from_body(&frame),
+        };
+
+        let (data_length, read) = match peek_var_int(&frame, 5)? {
+            Some(peeked) => peeked,
+            None => anyhow::bail!("Unexpected EOF while reading data length."),
+        };
+        frame.advance(read);
+        if data_length == 0 {
+            return RawPacket::from_body(&frame);
+        }
+
+        let data_length = data_length as usize;
+        if data_length < threshold || data_length > MAX_UNCOMPRESSED_LENGTH {
+            anyhow::bail!(
+                "Malformed data length {} for compression threshold {}.",
+                data_length,
+                threshold
+            );
+        }
+        let mut body = Vec::with_capacity(data_length);
+        flate2::read::ZlibDecoder::new(&frame[..])
+            .take(data_length as u64 + 1)
+            .read_to_end(&mut body)
+            .context("Failed to decompress packet.")?;
+        if body.len() != data_length {
+            anyhow::bail!(
+                "Expected {} decompressed bytes but found {}.",
+                data_length,
+                body.len()
+            );
+        }
+        RawPacket::from_body(&body)
+    }
+
+    fn encode_body(&self, packet: &RawPacket, dst: &mut BytesMut) -> anyhow::Result<()> {
+        let mut body = Vec::with_capacity(packet.data.len() + 5);
+        packet.id.encode_buf(&mut body)?;
+        body.extend_from_slice(&packet.data);
+
+        let frame = match self.compression_threshold {
+            None => body,
+            Some(threshold) if body.len() < threshold => {
+                let mut frame = Vec::with_capacity(body.len() + 1);
+                frame.push(0);
+                frame.extend_from_slice(&body);
+                frame
+            }
+            Some(_) => {
+                let mut frame = Vec::with_capacity(body.len() / 2 + 5);
+                VarInt::try_from(body.len())?.encode_buf(&mut frame)?;
+                let mut encoder =
+                    flate2::write::ZlibEncoder::new(frame, flate2::Compression::default());
+                encoder.write_all(&body)?;
+                encoder.finish().context("Failed to compress packet.")?
+            }
+        };
+
+        if frame.len() > MAX_PACKET_LENGTH as usize {
+            anyhow::bail!(
+                "Failed to frame packet with length {}, limit is {}.",
+                frame.len(),
+                MAX_PACKET_LENGTH
+            );
+        }
+        let length = VarInt::try_from(frame.len())?;
+        dst.reserve(usize::try_from(length.size()?)? + frame.len());
+        length.encode_buf(dst)?;
+        dst.put_slice(&frame);
+        Ok(())
+    }
+}
+
+impl Decoder for MinecraftCodec {
+    type Item = RawPacket;
+    type Error = anyhow::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> anyhow::Result<Option<RawPacket>> {
+        if let Some(decryptor) = &mut self.decryptor {
+            for byte in src[self.decrypted..].chunks_mut(1) {
+                decryptor.decrypt_block_mut(GenericArray::from_mut_slice(byte));
+            }
+            self.decrypted = src.len();
+        }
+
+        let (length, read) = match peek_var_int(src, 3)? {
+            Some(peeked) => peeked,
+            None => return Ok(None),
+        };
+        if !(1..=MAX_PACKET_LENGTH).contains(&length) {
+            anyhow::bail!("Malformed packet length found. Length {}", length);
+        }
+        let frame_length = read + length as usize;
+        if src.len() < frame_length {
+            src.reserve(frame_length - src.len());
+            return Ok(None);
+        }
+
+        src.advance(read);
+        let frame = src.split_to(length as usize);
+        if self.decryptor.is_some() {
+            self.decrypted -= frame_length;
+        }
+        self.decode_body(frame).map(Some)
+    }
+}
+
+impl Encoder<RawPacket> for MinecraftCodec {
+    type Error = anyhow::Error;
+
+    fn encode(&mut self, packet: RawPacket, dst: &mut BytesMut) -> anyhow::Result<()> {
+        Encoder::<&RawPacket>::encode(self, &packet, dst)
+    }
+}
+
+impl Encoder<&RawPacket> for MinecraftCodec {
+    type Error = anyhow::Error;
+
+    fn encode(&mut self, packet: &RawPacket, dst: &mut BytesMut) -> anyhow::Result<()> {
+        let start = dst.len();
+        self.encode_body(packet, dst)?;
+        if let Some(encryptor) = &mut self.encryptor {
+            for byte in dst[start..].chunks_mut(1) {
+                encryptor.encrypt_block_mut(GenericArray::from_mut_slice(byte));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Layers a `PacketRegistry` and `ConnectionStateMachine` over `MinecraftCodec`, decoding
+/// typed packets from one side of a connection.
+pub struct TypedCodec<P> {
+    codec: MinecraftCodec,
+    registry: Arc<PacketRegistry<P>>,
+    machine: ConnectionStateMachine,
+    inbound: Direction,
+    protocol_version: Option<i32>,
+    limits: DecodeLimits,
+}
+
+impl<P> TypedCodec<P> {
+    /// `inbound` is the direction of the packets this codec decodes, e.g. a server decodes
+    /// serverbound packets.
+    pub fn new(registry: Arc<PacketRegistry<P>>, inbound: Direction) -> Self {
+        TypedCodec {
+            codec: MinecraftCodec::new(),
+            registry,
+            machine: ConnectionStateMachine::new(),
+            inbound,
+            protocol_version: None,
+            limits: DecodeLimits::default(),
+        }
+    }
+
+    /// Pins the protocol version used for registry lookups instead of taking it from the
+    /// handshake.
+    pub fn with_protocol_version(mut self, protocol_version: i32) -> Self {
+        self.protocol_version = Some(protocol_version);
+        self
+    }
+
+    /// Replaces the default `DecodeLimits` each typed packet is decoded under.
+    pub fn with_limits(mut self, limits: DecodeLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub fn codec(&self) -> &MinecraftCodec {
+        &self.codec
+    }
+
+    pub fn codec_mut(&mut self) -> &mut MinecraftCodec {
+        &mut self.codec
+    }
+
+    pub fn state_machine(&self) -> &ConnectionStateMachine {
+        &self.machine
+    }
+
+    pub fn state_machine_mut(&mut self) -> &mut ConnectionStateMachine {
+        &mut self.machine
+    }
+}
+
+impl<P> Decoder for TypedCodec<P> {
+    type Item = (ConnectionState, DecodedPacket<P>);
+    type Error = anyhow::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> anyhow::Result<Option<Self::Item>> {
+        let packet = match self.codec.decode(src)? {
+            Some(packet) => packet,
+            None => return Ok(None),
+        };
+        let state = self.machine.observe(self.inbound, &packet)?;
+        let protocol_version = self
+            .protocol_version
+            .or_else(|| self.machine.protocol_version())
+            .unwrap_or_default();
+        let decoded = self.registry.decode_limited(
+            protocol_version,
+            state,
+            self.inbound,
+            packet,
+            &self.limits,
+        )?;
+        Ok(Some((state, decoded)))
+    }
+}
+
+impl<P> Encoder<RawPacket> for TypedCodec<P> {
+    type Error = anyhow::Error;
+
+    fn encode(&mut self, packet: RawPacket, dst: &mut BytesMut) -> anyhow::Result<()> {
+        self.machine.observe(self.inbound.opposite(), &packet)?;
+        self.codec.encode(packet, dst)
+    }
+}
+
+fn peek_var_int(bytes: &[u8], max_bytes: usize) -> anyhow::Result<Option<(i32, usize)>> {
+    if !bytes.iter().take(max_bytes).any(|byte| byte & 0x80 == 0) {
+        if bytes.len() >= max_bytes {
+            anyhow::bail!("Failed to decode VarInt, too many bytes.");
+        }
+        return Ok(None);
+    }
+    let (size, value) = VarInt::decode_and_size(&mut &bytes[..])?;
+    Ok(Some((*value, *size as usize)))
+}
@@ -0,0 +1,542 @@
+use crate::encoder::*;
+use crate::limits::DecodeLimits;
+use crate::nums::VarInt;
+use anyhow::Context;
+use std::convert::TryFrom;
+use std::io::{Read, Write};
+use std::marker::PhantomData;
+use tokio::io::AsyncWrite;
+
+/// The most items reserved up front for a collection; the rest grow as items actually decode, so
+/// a forged length cannot allocate more than the input backs.
+const MAX_PREALLOCATED_ITEMS: usize = 1024;
+
+/// Checks a declared collection length against `limits` and reserves what the input can back.
+pub(crate) fn reserve_items<T>(length: usize, limits: &mut DecodeLimits) -> anyhow::Result<Vec<T>> {
+    limits.collection(length, std::mem::size_of::<T>())?;
+    Ok(Vec::with_capacity(length.min(MAX_PREALLOCATED_ITEMS)))
+}
+
+macro_rules! vec_wrapper {
+    ([$($generics:tt)*] $name:ty) => {
+        impl<$($generics)*> std::ops::Deref for $name {
+            type Target = Vec<T>;
+
+            fn deref(&self) -> &Self::Target {
+                &self.0
+            }
+        }
+
+        impl<$($generics)*> std::ops::DerefMut for $name {
+            fn deref_mut(&mut self) -> &mut Self::Target {
+                &mut self.0
+            }
+        }
+
+        impl<$($generics)*> From<$name> for Vec<T> {
+            fn from(wrapper: $name) -> Self {
+                wrapper.0
+            }
+        }
+    };
+}
+
+/// Items decoded until the end of a bounded frame, e.g. the rest of a `RawPacket` body.
+///
+/// Only use this last in a packet: over an unbounded reader such as a socket it never ends.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct Remaining<T>(pub Vec<T>);
+
+vec_wrapper!([T] Remaining<T>);
+
+impl<T> From<Vec<T>> for Remaining<T> {
+    fn from(items: Vec<T>) -> Self {
+        Remaining(items)
+    }
+}
+
+/// An item which reads no bytes would otherwise repeat forever.
+fn check_progress(before: u64, after: u64) -> anyhow::Result<()> {
+    if before == after {
+        anyhow::bail!("Failed to decode remaining items, an item consumed no bytes.");
+    }
+    Ok(())
+}
+
+/// Reads the rest of a reader, charging it against `limits` and stopping one byte past the
+/// budget rather than reading an unbounded reader to its end.
+fn read_remaining<R: Read>(reader: &mut R, limits: &mut DecodeLimits) -> anyhow::Result<Vec<u8>> {
+    let budget = limits
+        .max_packet_allocation
+        .saturating_sub(limits.allocated());
+    let mut bytes = Vec::new();
+    reader
+        .by_ref()
+        .take((budget as u64).saturating_add(1))
+        .read_to_end(&mut bytes)
+        .context("Failed to read remaining bytes.")?;
+    limits.allocate(bytes.len())?;
+    Ok(bytes)
+}
+
+impl<T: Decodable> Decodable for Remaining<T> {
+    fn decode<R: Read>(reader: &mut R) -> anyhow::Result<Self> {
+        Self::decode_limited(reader, &mut DecodeLimits::default())
+    }
+
+    fn decode_buf<B: bytes::Buf>(buf: &mut B) -> anyhow::Result<Self> {
+        Self::decode_buf_limited(buf, &mut DecodeLimits::default())
+    }
+
+    fn decode_buf_limited<B: bytes::Buf>(
+        buf: &mut B,
+        limits: &mut DecodeLimits,
+    ) -> anyhow::Result<Self> {
+        let mut items: Vec<T> = Vec::new();
+        while buf.has_remaining() {
+            let before = buf.remaining();
+            items.push(T::decode_buf_limited(buf, limits)?);
+            check_progress(before as u64, buf.remaining() as u64)?;
+        }
+        Ok(Remaining(items))
+    }
+
+    fn decode_limited<R: Read>(reader: &mut R, limits: &mut DecodeLimits) -> anyhow::Result<Self> {
+        let mut items: Vec<T> = Vec::new();
+        let remaining_bytes = read_remaining(reader, limits)?;
+        let length = remaining_bytes.len() as u64;
+        let mut cursor = std::io::Cursor::new(remaining_bytes);
+
+        while cursor.position() < length {
+            let before = cursor.position();
+            items.push(T::decode_limited(&mut cursor, limits)?);
+            check_progress(length - before, length - cursor.position())?;
+        }
+        Ok(Remaining(items))
+    }
+}
+
+impl<T: Encodable> Encodable for Remaining<T> {
+    fn encode<W: Write>(&self, writer: &mut W) -> anyhow::Result<()> {
+        self.0.encode(writer)
+    }
+
+    fn size(&self) -> anyhow::Result<VarInt> {
+        self.0.size()
+    }
+
+    fn encode_buf<B: bytes::BufMut>(&self, buf: &mut B) -> anyhow::Result<()> {
+        self.0.encode_buf(buf)
+    }
+}
+
+#[async_trait::async_trait]
+impl<T: AsyncEncodable> AsyncEncodable for Remaining<T> {
+    async fn async_encode<W: AsyncWrite + Send + Unpin>(
+        &self,
+        writer: &mut W,
+    ) -> anyhow::Result<()> {
+        self.0.async_encode(writer).await
+    }
+}
+
+/// A length written ahead of a `Prefixed` collection.
+pub trait LengthPrefix: Decodable + Encodable {
+    fn from_length(length: usize) -> anyhow::Result<Self>;
+
+    fn to_length(&self) -> anyhow::Result<usize>;
+}
+
+macro_rules! length_prefix {
+    ($($prefix:ty),*) => {
+        $(
+            impl LengthPrefix for $prefix {
+                fn from_length(length: usize) -> anyhow::Result<Self> {
+                    <$prefix>::try_from(length).with_context(|| format!(
+                        "Failed to fit length {} into a {} prefix.",
+                        length,
+                        stringify!($prefix)
+                    ))
+                }
+
+                fn to_length(&self) -> anyhow::Result<usize> {
+                    usize::try_from(*self)
+                        .with_context(|| format!("Malformed collection length found. Length {}", self))
+                }
+            }
+        )*
+    };
+}
+
+length_prefix!(u8, i16, i32);
+
+impl LengthPrefix for VarInt {
+    fn from_length(length: usize) -> anyhow::Result<Self> {
+        VarInt::try_from(length)
+            .with_context(|| format!("Failed to fit length {} into a VarInt prefix.", length))
+    }
+
+    fn to_length(&self) -> anyhow::Result<usize> {
+        usize::try_from(**self)
+            .with_context(|| format!("Malformed collection length found. Length {}", self))
+    }
+}
+
+/// Items preceded by their count, written as `L`: a `VarInt`, `u8`, `i16` or `i32`.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Prefixed<L, T>(pub Vec<T>, PhantomData<L>);
+
+vec_wrapper!([L, T] Prefixed<L, T>);
+
+impl<L, T> Prefixed<L, T> {
+    pub fn new(items: Vec<T>) -> Self {
+        Prefixed(items, PhantomData)
+    }
+}
+
+impl<L, T> Default for Prefixed<L, T> {
+    fn default() -> Self {
+        Prefixed::new(Vec::new())
+    }
+}
+
+impl<L, T> From<Vec<T>> for Prefixed<L, T> {
+    fn from(items: Vec<T>) -> Self {
+        Prefixed::new(items)
+    }
+}
+
+impl<L: LengthPrefix, T: Decodable> Decodable for Prefixed<L, T> {
+    fn decode<R: Read>(reader: &mut R) -> anyhow::Result<Self> {
+        Self::decode_limited(reader, &mut DecodeLimits::default())
+    }
+
+    fn decode_buf<B: bytes::Buf>(buf: &mut B) -> anyhow::Result<Self> {
+        Self::decode_buf_limited(buf, &mut DecodeLimits::default())
+    }
+
+    fn decode_buf_limited<B: bytes::Buf>(
+        buf: &mut B,
+        limits: &mut DecodeLimits,
+    ) -> anyhow::Result<Self> {
+        let length = L::decode_buf(buf)?.to_length()?;
+        Ok(Prefixed::new(T::decode_many_buf_limited(
+            buf, length, limits,
+        )?))
+    }
+
+    fn decode_limited<R: Read>(reader: &mut R, limits: &mut DecodeLimits) -> anyhow::Result<Self> {
+        let length = L::decode(reader)?.to_length()?;
+        Ok(Prefixed::new(T::decode_many(reader, length, limits)?))
+    }
+}
+
+impl<L: LengthPrefix, T: Encodable> Encodable for Prefixed<L, T> {
+    fn encode<W: Write>(&self, writer: &mut W) -> anyhow::Result<()> {
+        L::from_length(self.0.len())?.encode(writer)?;
+        self.0.encode(writer)
+    }
+
+    fn size(&self) -> anyhow::Result<VarInt> {
+        L::from_length(self.0.len())?
+            .size()?
+            .try_add(self.0.size()?)
+    }
+
+    fn encode_buf<B: bytes::BufMut>(&self, buf: &mut B) -> anyhow::Result<()> {
+        L::from_length(self.0.len())?.encode_buf(buf)?;
+        self.0.encode_buf(buf)
+    }
+}
+
+#[async_trait::async_trait]
+impl<L: LengthPrefix + AsyncEncodable, T: AsyncEncodable> AsyncEncodable for Prefixed<L, T> {
+    async fn async_encode<W: AsyncWrite + Send + Unpin>(
+        &self,
+        writer: &mut W,
+    ) -> anyhow::Result<()> {
+        L::from_length(self.0.len())?.async_encode(writer).await?;
+        self.0.async_encode(writer).await
+    }
+}
+
+/// Exactly `N` items with no length on the wire, the count being known from context.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct Fixed<const N: usize, T>(pub Vec<T>);
+
+vec_wrapper!([const N: usize, T] Fixed<N, T>);
+
+impl<const N: usize, T> From<Vec<T>> for Fixed<N, T> {
+    fn from(items: Vec<T>) -> Self {
+        Fixed(items)
+    }
+}
+
+impl<const N: usize, T> Fixed<N, T> {
+    fn check_length(&self) -> anyhow::Result<()> {
+        if self.0.len() != N {
+            anyhow::bail!("Expected {} items but found {}.", N, self.0.len());
+        }
+        Ok(())
+    }
+}
+
+impl<const N: usize, T: Decodable> Decodable for Fixed<N, T> {
+    fn decode<R: Read>(reader: &mut R) -> anyhow::Result<Self> {
+        Self::decode_limited(reader, &mut DecodeLimits::default())
+    }
+
+    fn decode_buf<B: bytes::Buf>(buf: &mut B) -> anyhow::Result<Self> {
+        Self::decode_buf_limited(buf, &mut DecodeLimits::default())
+    }
+
+    fn decode_buf_limited<B: bytes::Buf>(
+        buf: &mut B,
+        limits: &mut DecodeLimits,
+    ) -> anyhow::Result<Self> {
+        Ok(Fixed(T::decode_many_buf_limited(buf, N, limits)?))
+    }
+
+    fn decode_limited<R: Read>(reader: &mut R, limits: &mut DecodeLimits) -> anyhow::Result<Self> {
+        Ok(Fixed(T::decode_many(reader, N, limits)?))
+    }
+}
+
+impl<const N: usize, T: Encodable> Encodable for Fixed<N, T> {
+    fn encode<W: Write>(&self, writer: &mut W) -> anyhow::Result<()> {
+        self.check_length()?;
+        self.0.encode(writer)
+    }
+
+    fn size(&self) -> anyhow::Result<VarInt> {
+        self.check_length()?;
+        self.0.size()
+    }
+
+    fn encode_buf<B: bytes::BufMut>(&self, buf: &mut B) -> anyhow::Result<()> {
+        self.check_length()?;
+        self.0.encode_buf(buf)
+    }
+}
+
+#[async_trait::async_trait]
+impl<const N: usize, T: AsyncEncodable> AsyncEncodable for Fixed<N, T> {
+    async fn async_encode<W: AsyncWrite + Send + Unpin>(
+        &self,
+        writer: &mut W,
+    ) -> anyhow::Result<()> {
+        self.check_length()?;
+        self.0.async_encode(writer).await
+    }
+}
+
+/// Raw bytes running to the end of a bounded frame, read and written in bulk.
+///
+/// Nothing on the wire says where these end, so `(VarInt, ByteArray)` is a VarInt followed by
+/// the rest of the frame. A length-prefixed byte array is `PrefixedBytes`.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct ByteArray(pub Vec<u8>);
+
+impl std::ops::Deref for ByteArray {
+    type Target = Vec<u8>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl std::ops::DerefMut for ByteArray {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl From<Vec<u8>> for ByteArray {
+    fn from(bytes: Vec<u8>) -> Self {
+        ByteArray(bytes)
+    }
+}
+
+impl From<ByteArray> for Vec<u8> {
+    fn from(bytes: ByteArray) -> Self {
+        bytes.0
+    }
+}
+
+impl Decodable for ByteArray {
+    fn decode<R: Read>(reader: &mut R) -> anyhow::Result<Self> {
+        Self::decode_limited(reader, &mut DecodeLimits::default())
+    }
+
+    fn decode_buf<B: bytes::Buf>(buf: &mut B) -> anyhow::Result<Self> {
+        Self::decode_buf_limited(buf, &mut DecodeLimits::default())
+    }
+
+    fn decode_buf_limited<B: bytes::Buf>(
+        buf: &mut B,
+        limits: &mut DecodeLimits,
+    ) -> anyhow::Result<Self> {
+        limits.allocate(buf.remaining())?;
+        let mut bytes = vec![0u8; buf.remaining()];
+        buf.copy_to_slice(&mut bytes);
+        Ok(ByteArray(bytes))
+    }
+
+    fn decode_limited<R: Read>(reader: &mut R, limits: &mut DecodeLimits) -> anyhow::Result<Self> {
+        Ok(ByteArray(read_remaining(reader, limits)?))
+    }
+}
+
+impl Encodable for ByteArray {
+    fn encode<W: Write>(&self, writer: &mut W) -> anyhow::Result<()> {
+        writer
+            .write_all(&self.0)
+            .with_context(|| format!("Failed to write {} bytes into buffer.", self.0.len()))
+    }
+
+    fn size(&self) -> anyhow::Result<VarInt> {
+        VarInt::try_from(self.0.len())
+            .with_context(|| format!("Failed to size {} bytes.", self.0.len()))
+    }
+
+    fn encode_buf<B: bytes::BufMut>(&self, buf: &mut B) -> anyhow::Result<()> {
+        check_remaining_mut(buf, self.0.len(), "bytes")?;
+        buf.put_slice(&self.0);
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl AsyncEncodable for ByteArray {
+    async fn async_encode<W: AsyncWrite + Send + Unpin>(
+        &self,
+        writer: &mut W,
+    ) -> anyhow::Result<()> {
+        use tokio::io::AsyncWriteExt;
+        writer
+            .write_all(&self.0)
+            .await
+            .with_context(|| format!("Failed to write {} bytes into buffer.", self.0.len()))
+    }
+}
+
+/// Bytes preceded by their count, written as `L`, read and written in bulk.
+///
+/// The same wire format as `Prefixed<L, u8>` without going through the per-item paths.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct PrefixedBytes<L>(pub Vec<u8>, PhantomData<L>);
+
+impl<L> PrefixedBytes<L> {
+    pub fn new(bytes: Vec<u8>) -> Self {
+        PrefixedBytes(bytes, PhantomData)
+    }
+}
+
+impl<L> Default for PrefixedBytes<L> {
+    fn default() -> Self {
+        PrefixedBytes::new(Vec::new())
+    }
+}
+
+impl<L> std::ops::Deref for PrefixedBytes<L> {
+    type Target = Vec<u8>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<L> std::ops::DerefMut for PrefixedBytes<L> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<L> From<Vec<u8>> for PrefixedBytes<L> {
+    fn from(bytes: Vec<u8>) -> Self {
+        PrefixedBytes::new(bytes)
+    }
+}
+
+impl<L> From<PrefixedBytes<L>> for Vec<u8> {
+    fn from(bytes: PrefixedBytes<L>) -> Self {
+        bytes.0
+    }
+}
+
+/// The most bytes read at once, so a forged length cannot allocate more than the input backs.
+const MAX_BYTES_CHUNK: usize = 65536;
+
+impl<L: LengthPrefix> Decodable for PrefixedBytes<L> {
+    fn decode<R: Read>(reader: &mut R) -> anyhow::Result<Self> {
+        Self::decode_limited(reader, &mut DecodeLimits::default())
+    }
+
+    fn decode_buf<B: bytes::Buf>(buf: &mut B) -> anyhow::Result<Self> {
+        Self::decode_buf_limited(buf, &mut DecodeLimits::default())
+    }
+
+    fn decode_buf_limited<B: bytes::Buf>(
+        buf: &mut B,
+        limits: &mut DecodeLimits,
+    ) -> anyhow::Result<Self> {
+        let length = L::decode_buf(buf)?.to_length()?;
+        check_remaining(buf, length, "bytes")?;
+        limits.collection(length, 1)?;
+        let mut bytes = vec![0u8; length];
+        buf.copy_to_slice(&mut bytes);
+        Ok(PrefixedBytes::new(bytes))
+    }
+
+    fn decode_limited<R: Read>(reader: &mut R, limits: &mut DecodeLimits) -> anyhow::Result<Self> {
+        let length = L::decode(reader)?.to_length()?;
+        limits.collection(length, 1)?;
+        let mut bytes = Vec::with_capacity(length.min(MAX_BYTES_CHUNK));
+        while bytes.len() < length {
+            let start = bytes.len();
+            bytes.resize(start + (length - start).min(MAX_BYTES_CHUNK), 0);
+            reader.read_exact(&mut bytes[start..]).with_context(|| {
+                format!("Unexpected EOF while reading {} bytes from buffer.", length)
+            })?;
+        }
+        Ok(PrefixedBytes::new(bytes))
+    }
+}
+
+impl<L: LengthPrefix> Encodable for PrefixedBytes<L> {
+    fn encode<W: Write>(&self, writer: &mut W) -> anyhow::Result<()> {
+        L::from_length(self.0.len())?.encode(writer)?;
+        writer
+            .write_all(&self.0)
+            .with_context(|| format!("Failed to write {} bytes into buffer.", self.0.len()))
+    }
+
+    fn size(&self) -> anyhow::Result<VarInt> {
+        L::from_length(self.0.len())?.size()?.try_add(
+            VarInt::try_from(self.0.len())
+                .with_context(|| format!("Failed to size {} bytes.", self.0.len()))?,
+        )
+    }
+
+    fn encode_buf<B: bytes::BufMut>(&self, buf: &mut B) -> anyhow::Result<()> {
+        L::from_length(self.0.len())?.encode_buf(buf)?;
+        check_remaining_mut(buf, self.0.len(), "bytes")?;
+        buf.put_slice(&self.0);
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl<L: LengthPrefix + AsyncEncodable> AsyncEncodable for PrefixedBytes<L> {
+    async fn async_encode<W: AsyncWrite + Send + Unpin>(
+        &self,
+        writer: &mut W,
+    ) -> anyhow::Result<()> {
+        use tokio::io::AsyncWriteExt;
+        L::from_length(self.0.len())?.async_encode(writer).await?;
+        writer
+            .write_all(&self.0)
+            .await
+            .with_context(|| format!("Failed to write {} bytes into buffer.", self.0.len()))
+    }
+}
@@ -1,18 +1,99 @@
 pub trait Decodable: Sized {
     fn decode<R: std::io::Read>(reader: &mut R) -> anyhow::Result<Self>;
-}
 
-pub trait SizeDecodable: Sized {
-    fn decode_sized<R: std::io::Read>(
+    fn decode_buf<B: bytes::Buf>(buf: &mut B) -> anyhow::Result<Self> {
+        Self::decode(&mut bytes::Buf::reader(buf))
+    }
+
+    /// Decodes under `limits`. Types which allocate from lengths on the wire, or hold types
+    /// that do, override this to check and charge them.
+    fn decode_limited<R: std::io::Read>(
+        reader: &mut R,
+        _limits: &mut crate::limits::DecodeLimits,
+    ) -> anyhow::Result<Self> {
+        Self::decode(reader)
+    }
+
+    /// `decode_limited` for a `Buf`. Types which override `decode_limited` override this too.
+    fn decode_buf_limited<B: bytes::Buf>(
+        buf: &mut B,
+        _limits: &mut crate::limits::DecodeLimits,
+    ) -> anyhow::Result<Self> {
+        Self::decode_buf(buf)
+    }
+
+    /// Decodes `count` items in a row. Primitives override this to read them in bulk.
+    fn decode_many<R: std::io::Read>(
         reader: &mut R,
-        size: &crate::nums::VarInt,
-    ) -> anyhow::Result<Self>;
+        count: usize,
+        limits: &mut crate::limits::DecodeLimits,
+    ) -> anyhow::Result<Vec<Self>> {
+        let mut items = crate::collections::reserve_items(count, limits)?;
+        for _ in 0..count {
+            items.push(Self::decode_limited(reader, limits)?);
+        }
+        Ok(items)
+    }
+
+    fn decode_many_buf<B: bytes::Buf>(buf: &mut B, count: usize) -> anyhow::Result<Vec<Self>> {
+        Self::decode_many_buf_limited(buf, count, &mut crate::limits::DecodeLimits::default())
+    }
+
+    fn decode_many_buf_limited<B: bytes::Buf>(
+        buf: &mut B,
+        count: usize,
+        limits: &mut crate::limits::DecodeLimits,
+    ) -> anyhow::Result<Vec<Self>> {
+        let mut items = crate::collections::reserve_items(count, limits)?;
+        for _ in 0..count {
+            items.push(Self::decode_buf_limited(buf, limits)?);
+        }
+        Ok(items)
+    }
 }
 
 pub trait Encodable {
     fn encode<W: std::io::Write>(&self, writer: &mut W) -> anyhow::Result<()>;
 
     fn size(&self) -> anyhow::Result<crate::nums::VarInt>;
+
+    fn encode_buf<B: bytes::BufMut>(&self, buf: &mut B) -> anyhow::Result<()> {
+        self.encode(&mut bytes::BufMut::writer(buf))
+    }
+
+    /// Encodes `items` in a row. Primitives override this to write them in bulk.
+    fn encode_many<W: std::io::Write>(items: &[Self], writer: &mut W) -> anyhow::Result<()>
+    where
+        Self: Sized,
+    {
+        items.iter().try_for_each(|item| item.encode(writer))
+    }
+
+    fn size_many(items: &[Self]) -> anyhow::Result<crate::nums::VarInt>
+    where
+        Self: Sized,
+    {
+        items
+            .iter()
+            .try_fold(crate::nums::VarInt::from(0), |size, item| {
+                size.try_add(item.size()?)
+            })
+    }
+
+    fn encode_many_buf<B: bytes::BufMut>(items: &[Self], buf: &mut B) -> anyhow::Result<()>
+    where
+        Self: Sized,
+    {
+        items.iter().try_for_each(|item| item.encode_buf(buf))
+    }
+
+    /// Encodes into a buffer whose capacity is reserved up front from `size()`.
+    fn encode_to_bytes(&self) -> anyhow::Result<bytes::BytesMut> {
+        let size = std::convert::TryFrom::try_from(self.size()?)?;
+        let mut buf = bytes::BytesMut::with_capacity(size);
+        self.encode_buf(&mut buf)?;
+        Ok(buf)
+    }
 }
 
 #[async_trait::async_trait]
@@ -21,23 +102,47 @@ pub trait AsyncEncodable: Encodable + Send + Sync {
         &self,
         writer: &mut W,
     ) -> anyhow::Result<()>;
-}
 
-pub trait SizeEncodable {
-    fn encode_sized<W: std::io::Write>(
-        &self,
+    async fn async_encode_many<W: tokio::io::AsyncWrite + Send + Unpin>(
+        items: &[Self],
         writer: &mut W,
-        size: &crate::nums::VarInt,
-    ) -> anyhow::Result<()>;
+    ) -> anyhow::Result<()>
+    where
+        Self: Sized,
+    {
+        for item in items {
+            item.async_encode(writer).await?;
+        }
+        Ok(())
+    }
+}
 
-    fn predicted_size(&self) -> anyhow::Result<crate::nums::VarInt>;
+/// Fails unless `buf` holds at least `needed` more bytes, as `Buf` getters panic on EOF.
+pub fn check_remaining<B: bytes::Buf>(buf: &B, needed: usize, reading: &str) -> anyhow::Result<()> {
+    if buf.remaining() < needed {
+        anyhow::bail!(
+            "Unexpected EOF while reading {} from buffer, needed {} bytes but found {}.",
+            reading,
+            needed,
+            buf.remaining()
+        );
+    }
+    Ok(())
 }
 
-#[async_trait::async_trait]
-pub trait AsyncSizeEncodable: SizeEncodable + Send + Sync {
-    async fn async_encode_sized<W: tokio::io::AsyncWrite + Send + Unpin>(
-        &self,
-        writer: &mut W,
-        size: &crate::nums::VarInt,
-    ) -> anyhow::Result<()>;
+/// Fails unless `buf` can take `needed` more bytes, as `BufMut` writers panic when full.
+pub fn check_remaining_mut<B: bytes::BufMut>(
+    buf: &B,
+    needed: usize,
+    writing: &str,
+) -> anyhow::Result<()> {
+    if buf.remaining_mut() < needed {
+        anyhow::bail!(
+            "Failed to write {} into buffer, needed {} bytes but found {}.",
+            writing,
+            needed,
+            buf.remaining_mut()
+        );
+    }
+    Ok(())
 }
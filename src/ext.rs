@@ -1,38 +1,24 @@
 use crate::encoder::*;
+use crate::limits::DecodeLimits;
 use crate::nums::VarInt;
 use anyhow::Context;
 use nbt::Blob;
-use std::convert::TryInto;
+use std::convert::{TryFrom, TryInto};
 use std::io::{Read, Write};
 use tokio::io::{AsyncWrite, AsyncWriteExt};
 use uuid::Uuid;
 
-impl<T: Decodable> Decodable for Vec<T> {
-    fn decode<R: Read>(reader: &mut R) -> anyhow::Result<Self> {
-        let mut items: Vec<T> = Vec::new();
-        let mut remaining_bytes = Vec::new();
-        let length = reader.read_to_end(&mut remaining_bytes)? as u64;
-        let mut cursor = std::io::Cursor::new(remaining_bytes);
-
-        while cursor.position() < length {
-            items.push(T::decode(&mut cursor)?);
-        }
-        Ok(items)
-    }
-}
-
 impl<T: Encodable> Encodable for Vec<T> {
     fn encode<W: Write>(&self, writer: &mut W) -> anyhow::Result<()> {
-        for item in self {
-            item.encode(writer)?;
-        }
-        Ok(())
+        T::encode_many(self, writer)
     }
 
     fn size(&self) -> anyhow::Result<VarInt> {
-        self.iter()
-            .map(|item| item.size())
-            .try_fold(0.into(), |bubble, item| Ok(bubble + item?))
+        T::size_many(self)
+    }
+
+    fn encode_buf<B: bytes::BufMut>(&self, buf: &mut B) -> anyhow::Result<()> {
+        T::encode_many_buf(self, buf)
     }
 }
 
@@ -42,82 +28,59 @@ impl<T: AsyncEncodable> AsyncEncodable for Vec<T> {
         &self,
         writer: &mut W,
     ) -> anyhow::Result<()> {
-        for item in self {
-            item.async_encode(writer).await?;
-        }
-        Ok(())
+        T::async_encode_many(self, writer).await
     }
 }
 
-impl<T: Decodable> SizeDecodable for Vec<T> {
-    fn decode_sized<R: Read>(reader: &mut R, size: &VarInt) -> anyhow::Result<Self> {
-        let mut items = Vec::with_capacity(size.try_into()?);
-        for _ in 0..size.into() {
-            items.push(T::decode(reader)?);
-        }
-        Ok(items)
+impl<const N: usize, T: Decodable> Decodable for [T; N] {
+    fn decode<R: Read>(reader: &mut R) -> anyhow::Result<Self> {
+        Self::decode_limited(reader, &mut DecodeLimits::default())
     }
-}
 
-impl<T: Encodable> SizeEncodable for Vec<T> {
-    fn encode_sized<W: Write>(&self, writer: &mut W, size: &VarInt) -> anyhow::Result<()> {
-        size.encode(writer)?;
-        self.iter().try_for_each(|item| item.encode(writer))
+    fn decode_buf<B: bytes::Buf>(buf: &mut B) -> anyhow::Result<Self> {
+        Self::decode_buf_limited(buf, &mut DecodeLimits::default())
     }
 
-    fn predicted_size(&self) -> anyhow::Result<VarInt> {
-        let mut size = self.size()?;
-        size += size.size()?;
-        Ok(size)
+    fn decode_buf_limited<B: bytes::Buf>(
+        buf: &mut B,
+        limits: &mut DecodeLimits,
+    ) -> anyhow::Result<Self> {
+        into_array(T::decode_many_buf_limited(buf, N, limits)?)
     }
-}
 
-#[async_trait::async_trait]
-impl<T: AsyncEncodable> AsyncSizeEncodable for Vec<T> {
-    async fn async_encode_sized<W: AsyncWrite + Send + Unpin>(
-        &self,
-        writer: &mut W,
-        size: &VarInt,
-    ) -> anyhow::Result<()> {
-        size.async_encode(writer).await?;
-        for item in self {
-            item.async_encode(writer).await?;
-        }
-        Ok(())
+    fn decode_limited<R: Read>(reader: &mut R, limits: &mut DecodeLimits) -> anyhow::Result<Self> {
+        into_array(T::decode_many(reader, N, limits)?)
     }
 }
 
-impl<T: SizeDecodable> Decodable for (VarInt, T) {
-    fn decode<R: Read>(reader: &mut R) -> anyhow::Result<Self> {
-        let size = VarInt::decode(reader)?;
-        let item = T::decode_sized(reader, &size)?;
-        Ok((size, item))
-    }
+fn into_array<const N: usize, T>(items: Vec<T>) -> anyhow::Result<[T; N]> {
+    let length = items.len();
+    items
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("Expected {} items but found {}.", N, length))
 }
 
-impl<T: SizeEncodable> Encodable for (VarInt, T) {
+impl<const N: usize, T: Encodable> Encodable for [T; N] {
     fn encode<W: Write>(&self, writer: &mut W) -> anyhow::Result<()> {
-        self.1.encode_sized(writer, &self.0)
+        T::encode_many(self, writer)
     }
 
     fn size(&self) -> anyhow::Result<VarInt> {
-        self.1.predicted_size()
+        T::size_many(self)
+    }
+
+    fn encode_buf<B: bytes::BufMut>(&self, buf: &mut B) -> anyhow::Result<()> {
+        T::encode_many_buf(self, buf)
     }
 }
 
 #[async_trait::async_trait]
-impl<T: AsyncSizeEncodable + Send + Sync> AsyncEncodable for (VarInt, T) {
+impl<const N: usize, T: AsyncEncodable> AsyncEncodable for [T; N] {
     async fn async_encode<W: AsyncWrite + Send + Unpin>(
         &self,
         writer: &mut W,
     ) -> anyhow::Result<()> {
-        self.1.async_encode_sized(writer, &self.0).await
-    }
-}
-
-impl<T: Decodable> Decodable for Option<T> {
-    fn decode<R: Read>(reader: &mut R) -> anyhow::Result<Self> {
-        Ok(Some(T::decode(reader)?))
+        T::async_encode_many(self, writer).await
     }
 }
 
@@ -130,6 +93,10 @@ impl<T: Encodable> Encodable for Option<T> {
         self.as_ref()
             .map_or(Ok(VarInt::from(0)), |item| item.size())
     }
+
+    fn encode_buf<B: bytes::BufMut>(&self, buf: &mut B) -> anyhow::Result<()> {
+        self.as_ref().map_or(Ok(()), |item| item.encode_buf(buf))
+    }
 }
 
 #[async_trait::async_trait]
@@ -146,97 +113,89 @@ impl<T: AsyncEncodable> AsyncEncodable for Option<T> {
     }
 }
 
-impl<T: Decodable> Decodable for (bool, Option<T>) {
-    fn decode<R: Read>(reader: &mut R) -> anyhow::Result<Self> {
-        let present = bool::decode(reader)?;
-        if present {
-            Ok((true, Some(T::decode(reader)?)))
-        } else {
-            Ok((false, None))
-        }
-    }
-}
+macro_rules! tuple_impls {
+    ($($name:ident $index:tt),+) => {
+        impl<$($name: Decodable),+> Decodable for ($($name,)+) {
+            fn decode<R: Read>(reader: &mut R) -> anyhow::Result<Self> {
+                Ok(($($name::decode(reader)?,)+))
+            }
 
-impl<T: Encodable> Encodable for (bool, Option<T>) {
-    fn encode<W: Write>(&self, writer: &mut W) -> anyhow::Result<()> {
-        self.0.encode(writer)?;
-        if self.0 {
-            match &self.1 {
-                Some(item) => item.encode(writer),
-                None => anyhow::bail!("Expected some value but found None."),
+            fn decode_buf<B: bytes::Buf>(buf: &mut B) -> anyhow::Result<Self> {
+                Ok(($($name::decode_buf(buf)?,)+))
             }
-        } else {
-            Ok(())
-        }
-    }
 
-    fn size(&self) -> anyhow::Result<VarInt> {
-        let size = self.0.size()?;
-        if self.0 {
-            match &self.1 {
-                Some(item) => Ok(size + item.size()?),
-                None => Ok(size),
+            fn decode_limited<R: Read>(
+                reader: &mut R,
+                limits: &mut DecodeLimits,
+            ) -> anyhow::Result<Self> {
+                Ok(($($name::decode_limited(reader, limits)?,)+))
             }
-        } else {
-            Ok(size)
-        }
-    }
-}
 
-#[async_trait::async_trait]
-impl<T: AsyncEncodable> AsyncEncodable for (bool, Option<T>) {
-    async fn async_encode<W: AsyncWrite + Send + Unpin>(
-        &self,
-        writer: &mut W,
-    ) -> anyhow::Result<()> {
-        self.0.async_encode(writer).await?;
-        if self.0 {
-            match &self.1 {
-                Some(item) => item.async_encode(writer).await,
-                None => anyhow::bail!("Expected some value but found None."),
+            fn decode_buf_limited<B: bytes::Buf>(
+                buf: &mut B,
+                limits: &mut DecodeLimits,
+            ) -> anyhow::Result<Self> {
+                Ok(($($name::decode_buf_limited(buf, limits)?,)+))
             }
-        } else {
-            Ok(())
         }
-    }
-}
 
-impl<X: Decodable, Y: Decodable, Z: Decodable> Decodable for (X, Y, Z) {
-    fn decode<R: Read>(reader: &mut R) -> anyhow::Result<Self> {
-        Ok((X::decode(reader)?, Y::decode(reader)?, Z::decode(reader)?))
-    }
-}
+        impl<$($name: Encodable),+> Encodable for ($($name,)+) {
+            fn encode<W: Write>(&self, writer: &mut W) -> anyhow::Result<()> {
+                $(self.$index.encode(writer)?;)+
+                Ok(())
+            }
 
-impl<X: Encodable, Y: Encodable, Z: Encodable> Encodable for (X, Y, Z) {
-    fn encode<W: Write>(&self, writer: &mut W) -> anyhow::Result<()> {
-        self.0.encode(writer)?;
-        self.1.encode(writer)?;
-        self.2.encode(writer)
-    }
+            fn size(&self) -> anyhow::Result<VarInt> {
+                let size = VarInt::from(0);
+                $(let size = size.try_add(self.$index.size()?)?;)+
+                Ok(size)
+            }
 
-    fn size(&self) -> anyhow::Result<VarInt> {
-        Ok(self.0.size()? + self.1.size()? + self.2.size()?)
-    }
-}
+            fn encode_buf<B: bytes::BufMut>(&self, buf: &mut B) -> anyhow::Result<()> {
+                $(self.$index.encode_buf(buf)?;)+
+                Ok(())
+            }
+        }
 
-#[async_trait::async_trait]
-impl<X: AsyncEncodable, Y: AsyncEncodable, Z: AsyncEncodable> AsyncEncodable for (X, Y, Z) {
-    async fn async_encode<W: AsyncWrite + Send + Unpin>(
-        &self,
-        writer: &mut W,
-    ) -> anyhow::Result<()> {
-        self.0.async_encode(writer).await?;
-        self.1.async_encode(writer).await?;
-        self.2.async_encode(writer).await
-    }
+        #[async_trait::async_trait]
+        impl<$($name: AsyncEncodable),+> AsyncEncodable for ($($name,)+) {
+            async fn async_encode<W: AsyncWrite + Send + Unpin>(
+                &self,
+                writer: &mut W,
+            ) -> anyhow::Result<()> {
+                $(self.$index.async_encode(writer).await?;)+
+                Ok(())
+            }
+        }
+    };
 }
 
+tuple_impls!(T0 0);
+tuple_impls!(T0 0, T1 1);
+tuple_impls!(T0 0, T1 1, T2 2);
+tuple_impls!(T0 0, T1 1, T2 2, T3 3);
+tuple_impls!(T0 0, T1 1, T2 2, T3 3, T4 4);
+tuple_impls!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5);
+tuple_impls!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6);
+tuple_impls!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7);
+tuple_impls!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8);
+tuple_impls!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9);
+tuple_impls!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9, T10 10);
+tuple_impls!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9, T10 10, T11 11);
+
 impl Decodable for Uuid {
     fn decode<R: Read>(reader: &mut R) -> anyhow::Result<Self> {
         let mut bytes: [u8; 16] = [0u8; 16];
         reader.read_exact(&mut bytes)?;
         Ok(Uuid::from_bytes(bytes))
     }
+
+    fn decode_buf<B: bytes::Buf>(buf: &mut B) -> anyhow::Result<Self> {
+        check_remaining(buf, 16, "Uuid")?;
+        let mut bytes: [u8; 16] = [0u8; 16];
+        buf.copy_to_slice(&mut bytes);
+        Ok(Uuid::from_bytes(bytes))
+    }
 }
 
 impl Encodable for Uuid {
@@ -249,6 +208,12 @@ impl Encodable for Uuid {
     fn size(&self) -> anyhow::Result<VarInt> {
         Ok(VarInt::from(16))
     }
+
+    fn encode_buf<B: bytes::BufMut>(&self, buf: &mut B) -> anyhow::Result<()> {
+        check_remaining_mut(buf, 16, "Uuid")?;
+        buf.put_slice(self.as_bytes());
+        Ok(())
+    }
 }
 
 #[async_trait::async_trait]
@@ -266,7 +231,19 @@ impl AsyncEncodable for Uuid {
 
 impl Decodable for Blob {
     fn decode<R: Read>(reader: &mut R) -> anyhow::Result<Self> {
-        Blob::from_reader(reader).context("Failed to read bytes into nbt string.")
+        Self::decode_limited(reader, &mut DecodeLimits::default())
+    }
+
+    fn decode_limited<R: Read>(reader: &mut R, limits: &mut DecodeLimits) -> anyhow::Result<Self> {
+        let bytes = NbtScanner::scan(reader, limits)?;
+        Blob::from_reader(&mut &bytes[..]).context("Failed to read bytes into nbt string.")
+    }
+
+    fn decode_buf_limited<B: bytes::Buf>(
+        buf: &mut B,
+        limits: &mut DecodeLimits,
+    ) -> anyhow::Result<Self> {
+        Self::decode_limited(&mut bytes::Buf::reader(buf), limits)
     }
 }
 
@@ -277,10 +254,14 @@ impl Encodable for Blob {
     }
 
     fn size(&self) -> anyhow::Result<VarInt> {
-        self.len_bytes().try_into().context(format!(
-            "Failed to turn {} into a VarInt.",
-            self.len_bytes()
-        ))
+        // `len_bytes` counts strings as UTF-8 but NBT writes them as modified UTF-8
+        let mut counter = ByteCounter(0);
+        self.to_writer(&mut counter)
+            .context("Failed to write nbt string into bytes.")?;
+        counter
+            .0
+            .try_into()
+            .context(format!("Failed to turn {} into a VarInt.", counter.0))
     }
 }
 
@@ -298,3 +279,125 @@ impl AsyncEncodable for Blob {
             .context("Failed to write nbt string into bytes.")
     }
 }
+
+struct ByteCounter(usize);
+
+impl Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Copies one NBT root out of a reader, checking tag ids, lengths, nesting and size against
+/// `DecodeLimits` on the way.
+///
+/// hematite-nbt preallocates from declared lengths and recurses without a limit, so it only ever
+/// sees bytes this has already walked.
+struct NbtScanner<'a, R: Read> {
+    reader: &'a mut R,
+    limits: &'a mut DecodeLimits,
+    bytes: Vec<u8>,
+}
+
+impl<'a, R: Read> NbtScanner<'a, R> {
+    fn scan(reader: &'a mut R, limits: &'a mut DecodeLimits) -> anyhow::Result<Vec<u8>> {
+        let mut scanner = NbtScanner {
+            reader,
+            limits,
+            bytes: Vec::new(),
+        };
+        let [tag] = scanner.copy_array::<1>()?;
+        if tag != 0x0a {
+            anyhow::bail!("Failed to decode nbt, root tag {} is not a compound.", tag);
+        }
+        scanner.string()?;
+        scanner.compound(1)?;
+        Ok(scanner.bytes)
+    }
+
+    fn copy(&mut self, length: u64) -> anyhow::Result<()> {
+        let bytes = usize::try_from(length).unwrap_or(usize::MAX);
+        self.limits.nbt_bytes(self.bytes.len(), bytes)?;
+        let read = self
+            .reader
+            .by_ref()
+            .take(length)
+            .read_to_end(&mut self.bytes)?;
+        if read as u64 != length {
+            anyhow::bail!("Unexpected EOF while decoding nbt.");
+        }
+        Ok(())
+    }
+
+    fn copy_array<const N: usize>(&mut self) -> anyhow::Result<[u8; N]> {
+        self.limits.nbt_bytes(self.bytes.len(), N)?;
+        let mut bytes = [0u8; N];
+        self.reader
+            .read_exact(&mut bytes)
+            .context("Unexpected EOF while decoding nbt.")?;
+        self.bytes.extend_from_slice(&bytes);
+        Ok(bytes)
+    }
+
+    fn length(&mut self) -> anyhow::Result<u64> {
+        let length = i32::from_be_bytes(self.copy_array()?);
+        u64::try_from(length)
+            .with_context(|| format!("Malformed nbt length found. Length {}", length))
+    }
+
+    fn string(&mut self) -> anyhow::Result<()> {
+        let length = u16::from_be_bytes(self.copy_array()?);
+        self.copy(u64::from(length))
+    }
+
+    fn payload(&mut self, tag: u8, depth: usize) -> anyhow::Result<()> {
+        match tag {
+            0x01 => self.copy(1),
+            0x02 => self.copy(2),
+            0x03 | 0x05 => self.copy(4),
+            0x04 | 0x06 => self.copy(8),
+            0x07 => self.array(1),
+            0x08 => self.string(),
+            0x09 => self.list(depth + 1),
+            0x0a => self.compound(depth + 1),
+            0x0b => self.array(4),
+            0x0c => self.array(8),
+            _ => anyhow::bail!("Failed to decode nbt, unknown tag id {}.", tag),
+        }
+    }
+
+    fn array(&mut self, width: u64) -> anyhow::Result<()> {
+        let length = self.length()?;
+        self.copy(length * width)
+    }
+
+    fn list(&mut self, depth: usize) -> anyhow::Result<()> {
+        self.limits.nbt_depth(depth)?;
+        let [tag] = self.copy_array::<1>()?;
+        let length = self.length()?;
+        if tag == 0x00 && length != 0 {
+            anyhow::bail!("Malformed nbt list of {} end tags.", length);
+        }
+        for _ in 0..length {
+            self.payload(tag, depth)?;
+        }
+        Ok(())
+    }
+
+    fn compound(&mut self, depth: usize) -> anyhow::Result<()> {
+        self.limits.nbt_depth(depth)?;
+        loop {
+            let [tag] = self.copy_array::<1>()?;
+            if tag == 0x00 {
+                return Ok(());
+            }
+            self.string()?;
+            self.payload(tag, depth)?;
+        }
+    }
+}
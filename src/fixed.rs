@@ -0,0 +1,162 @@
+use crate::encoder::*;
+use crate::nums::VarInt;
+use std::convert::TryFrom;
+use std::io::{Read, Write};
+use tokio::io::AsyncWrite;
+
+/// A fixed-point number with 5 fractional bits, the raw value being the real one times 32.
+///
+/// Pre-1.9 protocols send absolute entity positions as `FixedPoint5<i32>` and relative moves as
+/// `FixedPoint5<i8>`.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct FixedPoint5<T>(pub T);
+
+impl<T: Decodable> Decodable for FixedPoint5<T> {
+    fn decode<R: Read>(reader: &mut R) -> anyhow::Result<Self> {
+        Ok(FixedPoint5(T::decode(reader)?))
+    }
+
+    fn decode_buf<B: bytes::Buf>(buf: &mut B) -> anyhow::Result<Self> {
+        Ok(FixedPoint5(T::decode_buf(buf)?))
+    }
+}
+
+impl<T: Encodable> Encodable for FixedPoint5<T> {
+    fn encode<W: Write>(&self, writer: &mut W) -> anyhow::Result<()> {
+        self.0.encode(writer)
+    }
+
+    fn size(&self) -> anyhow::Result<VarInt> {
+        self.0.size()
+    }
+
+    fn encode_buf<B: bytes::BufMut>(&self, buf: &mut B) -> anyhow::Result<()> {
+        self.0.encode_buf(buf)
+    }
+}
+
+#[async_trait::async_trait]
+impl<T: AsyncEncodable> AsyncEncodable for FixedPoint5<T> {
+    async fn async_encode<W: AsyncWrite + Send + Unpin>(
+        &self,
+        writer: &mut W,
+    ) -> anyhow::Result<()> {
+        self.0.async_encode(writer).await
+    }
+}
+
+/// Scales `value` to the nearest raw value at or below it, failing when it doesn't fit.
+fn scale_floor(value: f64, scale: f64, min: f64, max: f64, name: &str) -> anyhow::Result<f64> {
+    let raw = (value * scale).floor();
+    if !(min..=max).contains(&raw) {
+        anyhow::bail!("Failed to convert {} to {}, out of range.", value, name);
+    }
+    Ok(raw)
+}
+
+macro_rules! declare_fixed_point {
+    ($($prim:ty),*) => {
+        $(
+            impl FixedPoint5<$prim> {
+                /// Converts like the vanilla server does, flooring `value * 32`. Fails when the
+                /// result is out of range or `value` is not finite.
+                pub fn from_f64(value: f64) -> anyhow::Result<Self> {
+                    let raw = scale_floor(
+                        value,
+                        32.0,
+                        <$prim>::MIN as f64,
+                        <$prim>::MAX as f64,
+                        concat!("FixedPoint5<", stringify!($prim), ">"),
+                    )?;
+                    Ok(FixedPoint5(raw as $prim))
+                }
+
+                pub fn to_f64(self) -> f64 {
+                    f64::from(self.0) / 32.0
+                }
+            }
+        )*
+    };
+}
+
+declare_fixed_point!(i8, i32);
+
+/// A relative move in 1/4096ths of a block, sent as a `short` since 1.9.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct PositionDelta(pub i16);
+
+impl PositionDelta {
+    /// The delta the vanilla server sends for a move, the difference of both positions floored
+    /// to 1/4096ths. Fails when the move is too far for a relative packet.
+    pub fn between(previous: f64, current: f64) -> anyhow::Result<Self> {
+        let previous = scale_floor(previous, 4096.0, i64::MIN as f64, i64::MAX as f64, "i64")?;
+        let current = scale_floor(current, 4096.0, i64::MIN as f64, i64::MAX as f64, "i64")?;
+        let delta = (current as i64)
+            .checked_sub(previous as i64)
+            .and_then(|delta| i16::try_from(delta).ok());
+        match delta {
+            Some(delta) => Ok(PositionDelta(delta)),
+            None => anyhow::bail!(
+                "Failed to encode a move of {} blocks as a delta.",
+                (current - previous) / 4096.0
+            ),
+        }
+    }
+
+    /// Floors `value * 4096`, failing when it is out of range or not finite.
+    pub fn from_f64(value: f64) -> anyhow::Result<Self> {
+        let raw = scale_floor(
+            value,
+            4096.0,
+            i16::MIN as f64,
+            i16::MAX as f64,
+            "PositionDelta",
+        )?;
+        Ok(PositionDelta(raw as i16))
+    }
+
+    pub fn to_f64(self) -> f64 {
+        f64::from(self.0) / 4096.0
+    }
+}
+
+/// Exact, as 1/32 is a multiple of 1/4096.
+impl From<FixedPoint5<i8>> for PositionDelta {
+    fn from(delta: FixedPoint5<i8>) -> Self {
+        PositionDelta(i16::from(delta.0) * 128)
+    }
+}
+
+impl Decodable for PositionDelta {
+    fn decode<R: Read>(reader: &mut R) -> anyhow::Result<Self> {
+        Ok(PositionDelta(i16::decode(reader)?))
+    }
+
+    fn decode_buf<B: bytes::Buf>(buf: &mut B) -> anyhow::Result<Self> {
+        Ok(PositionDelta(i16::decode_buf(buf)?))
+    }
+}
+
+impl Encodable for PositionDelta {
+    fn encode<W: Write>(&self, writer: &mut W) -> anyhow::Result<()> {
+        self.0.encode(writer)
+    }
+
+    fn size(&self) -> anyhow::Result<VarInt> {
+        self.0.size()
+    }
+
+    fn encode_buf<B: bytes::BufMut>(&self, buf: &mut B) -> anyhow::Result<()> {
+        self.0.encode_buf(buf)
+    }
+}
+
+#[async_trait::async_trait]
+impl AsyncEncodable for PositionDelta {
+    async fn async_encode<W: AsyncWrite + Send + Unpin>(
+        &self,
+        writer: &mut W,
+    ) -> anyhow::Result<()> {
+        self.0.async_encode(writer).await
+    }
+}
@@ -0,0 +1,312 @@
+use crate::encoder::*;
+use crate::nums::VarInt;
+use crate::packets::status::ServerStatus;
+use crate::strings::McString;
+use anyhow::Context;
+use std::convert::TryFrom;
+use std::io::{Read, Write};
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+/// The first byte of every pre-1.7 server list ping. Modern handshakes never start with it.
+pub const LEGACY_PING_PREFIX: u8 = 0xFE;
+pub const LEGACY_KICK_ID: u8 = 0xFF;
+const PING_HOST_CHANNEL: &str = "MC|PingHost";
+
+pub fn is_legacy_ping(first_byte: u8) -> bool {
+    first_byte == LEGACY_PING_PREFIX
+}
+
+pub fn encode_utf16_be(string: &str) -> Vec<u8> {
+    string
+        .encode_utf16()
+        .flat_map(|unit| unit.to_be_bytes())
+        .collect()
+}
+
+// `usize::is_multiple_of` needs Rust 1.87, which the crate does not require
+#[allow(clippy::manual_is_multiple_of)]
+pub fn decode_utf16_be(bytes: &[u8]) -> anyhow::Result<String> {
+    if bytes.len() % 2 != 0 {
+        anyhow::bail!("Malformed UTF-16 string with odd length {}.", bytes.len());
+    }
+    let units: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+        .collect();
+    String::from_utf16(&units).context("Failed to build UTF-16 encoded string.")
+}
+
+/// The pre-1.7 string: a `u16` count of UTF-16 code units followed by UTF-16BE data.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct LegacyString(pub String);
+
+impl LegacyString {
+    fn units(&self) -> anyhow::Result<u16> {
+        u16::try_from(self.0.encode_utf16().count()).context(format!(
+            "Failed to encode legacy string with {} characters.",
+            self.0.encode_utf16().count()
+        ))
+    }
+}
+
+impl From<&str> for LegacyString {
+    fn from(string: &str) -> Self {
+        LegacyString(string.to_string())
+    }
+}
+
+impl Decodable for LegacyString {
+    fn decode<R: Read>(reader: &mut R) -> anyhow::Result<Self> {
+        let units = u16::decode(reader)?;
+        let mut bytes = vec![0u8; usize::from(units) * 2];
+        reader.read_exact(&mut bytes).context(format!(
+            "Unexpected EOF while decoding legacy string with {} characters.",
+            units
+        ))?;
+        Ok(LegacyString(decode_utf16_be(&bytes)?))
+    }
+
+    fn decode_buf<B: bytes::Buf>(buf: &mut B) -> anyhow::Result<Self> {
+        let units = u16::decode_buf(buf)?;
+        check_remaining(buf, usize::from(units) * 2, "legacy string")?;
+        let mut bytes = vec![0u8; usize::from(units) * 2];
+        buf.copy_to_slice(&mut bytes);
+        Ok(LegacyString(decode_utf16_be(&bytes)?))
+    }
+}
+
+impl Encodable for LegacyString {
+    fn encode<W: Write>(&self, writer: &mut W) -> anyhow::Result<()> {
+        self.units()?.encode(writer)?;
+        writer
+            .write_all(&encode_utf16_be(&self.0))
+            .context("Failed to write legacy string into buffer.")
+    }
+
+    fn size(&self) -> anyhow::Result<VarInt> {
+        VarInt::from(2).try_add(VarInt::from(self.units()?) * 2)
+    }
+
+    fn encode_buf<B: bytes::BufMut>(&self, buf: &mut B) -> anyhow::Result<()> {
+        self.units()?.encode_buf(buf)?;
+        let bytes = encode_utf16_be(&self.0);
+        check_remaining_mut(buf, bytes.len(), "legacy string")?;
+        buf.put_slice(&bytes);
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl AsyncEncodable for LegacyString {
+    async fn async_encode<W: AsyncWrite + Send + Unpin>(
+        &self,
+        writer: &mut W,
+    ) -> anyhow::Result<()> {
+        self.units()?.async_encode(writer).await?;
+        writer
+            .write_all(&encode_utf16_be(&self.0))
+            .await
+            .context("Failed to write legacy string into buffer.")
+    }
+}
+
+/// What follows the `0xFE` prefix, which depends on the client version.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum LegacyPingRequest {
+    /// Beta 1.8 to 1.3 send the prefix alone.
+    Beta,
+    /// 1.4 and 1.5 send `0xFE 0x01`.
+    V1_4,
+    /// 1.6 follows `0xFE 0x01` with a `MC|PingHost` plugin message.
+    V1_6 {
+        protocol_version: u8,
+        hostname: String,
+        port: i32,
+    },
+}
+
+impl LegacyPingRequest {
+    /// Decodes the body of a 1.6 ping, everything after `0xFE 0x01`.
+    pub fn decode_ping_host<R: Read>(reader: &mut R) -> anyhow::Result<Self> {
+        let id = u8::decode(reader)?;
+        if id != 0xFA {
+            anyhow::bail!("Expected legacy plugin message but found packet id {}.", id);
+        }
+        let channel = LegacyString::decode(reader)?;
+        if channel.0 != PING_HOST_CHANNEL {
+            anyhow::bail!("Unexpected legacy ping channel {}.", channel.0);
+        }
+        let _length = u16::decode(reader)?;
+        Ok(LegacyPingRequest::V1_6 {
+            protocol_version: u8::decode(reader)?,
+            hostname: LegacyString::decode(reader)?.0,
+            port: i32::decode(reader)?,
+        })
+    }
+
+    pub fn encode<W: Write>(&self, writer: &mut W) -> anyhow::Result<()> {
+        LEGACY_PING_PREFIX.encode(writer)?;
+        match self {
+            LegacyPingRequest::Beta => Ok(()),
+            LegacyPingRequest::V1_4 => 1u8.encode(writer),
+            LegacyPingRequest::V1_6 {
+                protocol_version,
+                hostname,
+                port,
+            } => {
+                let hostname = LegacyString(hostname.clone());
+                let length = u16::try_from(*hostname.size()?.try_add(VarInt::from(5))?)?;
+                1u8.encode(writer)?;
+                0xFAu8.encode(writer)?;
+                LegacyString::from(PING_HOST_CHANNEL).encode(writer)?;
+                length.encode(writer)?;
+                protocol_version.encode(writer)?;
+                hostname.encode(writer)?;
+                port.encode(writer)
+            }
+        }
+    }
+}
+
+/// The kick packet sent in reply to a legacy ping.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LegacyPingResponse {
+    pub protocol_version: i32,
+    pub server_version: String,
+    pub motd: String,
+    pub online: i32,
+    pub max: i32,
+}
+
+impl LegacyPingResponse {
+    /// Flattens a modern status into the legacy fields, dropping any chat formatting.
+    pub fn from_status(status: &ServerStatus) -> Self {
+        let (online, max) = status
+            .players
+            .as_ref()
+            .map_or((0, 0), |players| (players.online, players.max));
+        let motd = serde_json::from_str(status.description.string())
+            .map(|value| plain_text(&value))
+            .unwrap_or_else(|_| status.description.string().clone());
+        LegacyPingResponse {
+            protocol_version: status.version.protocol,
+            server_version: status.version.name.clone(),
+            motd,
+            online,
+            max,
+        }
+    }
+
+    /// The `§1` prefixed form understood by 1.4 and newer clients.
+    pub fn kick_message(&self) -> String {
+        format!(
+            "\u{a7}1\0{}\0{}\0{}\0{}\0{}",
+            self.protocol_version, self.server_version, self.motd, self.online, self.max
+        )
+    }
+
+    /// The `motd§online§max` form understood by Beta 1.8 to 1.3 clients.
+    pub fn beta_kick_message(&self) -> String {
+        let motd: String = self.motd.chars().filter(|c| *c != '\u{a7}').collect();
+        format!("{}\u{a7}{}\u{a7}{}", motd, self.online, self.max)
+    }
+
+    /// Encodes the kick packet in the form the given request expects.
+    pub fn encode_for<W: Write>(
+        &self,
+        request: &LegacyPingRequest,
+        writer: &mut W,
+    ) -> anyhow::Result<()> {
+        let message = match request {
+            LegacyPingRequest::Beta => self.beta_kick_message(),
+            _ => self.kick_message(),
+        };
+        LEGACY_KICK_ID.encode(writer)?;
+        LegacyString(message).encode(writer)
+    }
+}
+
+impl Decodable for LegacyPingResponse {
+    fn decode<R: Read>(reader: &mut R) -> anyhow::Result<Self> {
+        let id = u8::decode(reader)?;
+        if id != LEGACY_KICK_ID {
+            anyhow::bail!("Expected legacy kick but found packet id {}.", id);
+        }
+        let message = LegacyString::decode(reader)?.0;
+        let number = |field: &str| {
+            field
+                .parse::<i32>()
+                .context(format!("Malformed legacy ping number {}.", field))
+        };
+
+        if let Some(fields) = message.strip_prefix("\u{a7}1\0") {
+            let fields: Vec<&str> = fields.split('\0').collect();
+            if fields.len() != 5 {
+                anyhow::bail!("Expected 5 legacy ping fields but found {}.", fields.len());
+            }
+            Ok(LegacyPingResponse {
+                protocol_version: number(fields[0])?,
+                server_version: fields[1].to_string(),
+                motd: fields[2].to_string(),
+                online: number(fields[3])?,
+                max: number(fields[4])?,
+            })
+        } else {
+            let fields: Vec<&str> = message.rsplitn(3, '\u{a7}').collect();
+            if fields.len() != 3 {
+                anyhow::bail!("Malformed beta legacy ping response {}.", message);
+            }
+            Ok(LegacyPingResponse {
+                protocol_version: 0,
+                server_version: String::new(),
+                motd: fields[2].to_string(),
+                online: number(fields[1])?,
+                max: number(fields[0])?,
+            })
+        }
+    }
+}
+
+impl Encodable for LegacyPingResponse {
+    fn encode<W: Write>(&self, writer: &mut W) -> anyhow::Result<()> {
+        self.encode_for(&LegacyPingRequest::V1_4, writer)
+    }
+
+    fn size(&self) -> anyhow::Result<VarInt> {
+        VarInt::from(1).try_add(LegacyString(self.kick_message()).size()?)
+    }
+}
+
+#[async_trait::async_trait]
+impl AsyncEncodable for LegacyPingResponse {
+    async fn async_encode<W: AsyncWrite + Send + Unpin>(
+        &self,
+        writer: &mut W,
+    ) -> anyhow::Result<()> {
+        let mut bytes = Vec::new();
+        self.encode(&mut bytes)?;
+        writer
+            .write_all(&bytes)
+            .await
+            .context("Failed to write legacy ping response into buffer.")
+    }
+}
+
+fn plain_text(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(text) => text.clone(),
+        serde_json::Value::Array(parts) => parts.iter().map(plain_text).collect(),
+        serde_json::Value::Object(component) => {
+            let mut text = component
+                .get("text")
+                .and_then(|text| text.as_str())
+                .unwrap_or_default()
+                .to_string();
+            if let Some(serde_json::Value::Array(extra)) = component.get("extra") {
+                text.extend(extra.iter().map(plain_text));
+            }
+            text
+        }
+        _ => String::new(),
+    }
+}
@@ -0,0 +1,289 @@
+use crate::common::Identifier;
+use crate::encoder::*;
+use crate::limits::DecodeLimits;
+use anyhow::Context;
+use nbt::{Blob, Map, Value};
+use std::io::Write;
+use std::path::Path;
+use uuid::Uuid;
+
+/// Reads a gzipped NBT file such as `level.dat` or `playerdata/<uuid>.dat`.
+pub fn read_gzip_nbt<P: AsRef<Path>>(path: P) -> anyhow::Result<Blob> {
+    read_gzip_nbt_limited(path, &mut DecodeLimits::default())
+}
+
+pub fn read_gzip_nbt_limited<P: AsRef<Path>>(
+    path: P,
+    limits: &mut DecodeLimits,
+) -> anyhow::Result<Blob> {
+    let path = path.as_ref();
+    let file = std::fs::File::open(path)
+        .with_context(|| format!("Failed to open nbt file {}.", path.display()))?;
+    Blob::decode_limited(
+        &mut flate2::read::GzDecoder::new(std::io::BufReader::new(file)),
+        limits,
+    )
+    .with_context(|| format!("Failed to read nbt file {}.", path.display()))
+}
+
+/// Writes a gzipped NBT file through a temporary file beside it, so a failed write leaves the
+/// old file in place.
+pub fn write_gzip_nbt<P: AsRef<Path>>(path: P, blob: &Blob) -> anyhow::Result<()> {
+    let path = path.as_ref();
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    blob.encode(&mut encoder)?;
+    let bytes = encoder.finish()?;
+
+    let mut temporary = path.as_os_str().to_owned();
+    temporary.push(".tmp");
+    let mut file = std::fs::File::create(&temporary)
+        .with_context(|| format!("Failed to create nbt file {}.", path.display()))?;
+    file.write_all(&bytes)?;
+    file.sync_all()?;
+    std::fs::rename(&temporary, path)
+        .with_context(|| format!("Failed to replace nbt file {}.", path.display()))
+}
+
+/// The root of a `Blob`, whose fields hematite only looks up by `&'static str`, or a compound
+/// nested inside one.
+#[derive(Clone, Copy)]
+enum Fields<'a> {
+    Root(&'a Blob),
+    Compound(&'a Map<String, Value>),
+}
+
+impl<'a> Fields<'a> {
+    fn get(self, name: &'static str) -> Option<&'a Value> {
+        match self {
+            Fields::Root(blob) => blob.get(name),
+            Fields::Compound(compound) => compound.get(name),
+        }
+    }
+}
+
+fn int(value: Option<&Value>) -> Option<i32> {
+    match value? {
+        Value::Byte(value) => Some(i32::from(*value)),
+        Value::Short(value) => Some(i32::from(*value)),
+        Value::Int(value) => Some(*value),
+        _ => None,
+    }
+}
+
+fn string(value: Option<&Value>) -> Option<&str> {
+    match value? {
+        Value::String(value) => Some(value),
+        _ => None,
+    }
+}
+
+fn compound(value: Option<&Value>) -> Option<&Map<String, Value>> {
+    match value? {
+        Value::Compound(value) => Some(value),
+        _ => None,
+    }
+}
+
+fn list(value: Option<&Value>) -> &[Value] {
+    match value {
+        Some(Value::List(values)) => values,
+        _ => &[],
+    }
+}
+
+/// The `Data` compound of a `level.dat`.
+pub struct LevelView<'a>(&'a Map<String, Value>);
+
+impl<'a> LevelView<'a> {
+    pub fn new(blob: &'a Blob) -> anyhow::Result<Self> {
+        match blob.get("Data") {
+            Some(Value::Compound(data)) => Ok(LevelView(data)),
+            _ => anyhow::bail!("Malformed level.dat found. No Data compound"),
+        }
+    }
+
+    pub fn data_version(&self) -> Option<i32> {
+        int(self.0.get("DataVersion"))
+    }
+
+    pub fn level_name(&self) -> Option<&'a str> {
+        string(self.0.get("LevelName"))
+    }
+
+    /// The name of the game version that last saved the world, such as `1.20.1`.
+    pub fn version_name(&self) -> Option<&'a str> {
+        string(compound(self.0.get("Version"))?.get("Name"))
+    }
+
+    pub fn spawn(&self) -> Option<(i32, i32, i32)> {
+        Some((
+            int(self.0.get("SpawnX"))?,
+            int(self.0.get("SpawnY"))?,
+            int(self.0.get("SpawnZ"))?,
+        ))
+    }
+
+    pub fn spawn_angle(&self) -> Option<f32> {
+        match self.0.get("SpawnAngle")? {
+            Value::Float(angle) => Some(*angle),
+            _ => None,
+        }
+    }
+
+    /// A game rule's value, which level.dat stores as a string whatever its type.
+    pub fn game_rule(&self, name: &str) -> Option<&'a str> {
+        string(compound(self.0.get("GameRules"))?.get(name))
+    }
+
+    pub fn game_rules(&self) -> impl Iterator<Item = (&'a str, &'a str)> {
+        compound(self.0.get("GameRules"))
+            .into_iter()
+            .flat_map(|rules| rules.iter())
+            .filter_map(|(name, value)| match value {
+                Value::String(value) => Some((name.as_str(), value.as_str())),
+                _ => None,
+            })
+    }
+
+    /// The player of a singleplayer world, who is saved here rather than in `playerdata`.
+    pub fn player(&self) -> Option<PlayerView<'a>> {
+        compound(self.0.get("Player")).map(PlayerView::from_compound)
+    }
+}
+
+/// What an item saved in an inventory carries besides its id and count, which 1.20.5 moved from
+/// `tag` to data components.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ItemData {
+    /// Up to 1.20.4: a `Count` byte and the optional `tag` compound.
+    Tag(Option<Map<String, Value>>),
+    /// Since 1.20.5: a `count` int and the optional `components` compound.
+    Components(Option<Map<String, Value>>),
+}
+
+/// An item stack as saved in an inventory list, rather than as sent over the network.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SavedItem {
+    pub slot: i8,
+    pub id: Identifier,
+    pub count: i32,
+    pub data: ItemData,
+}
+
+impl SavedItem {
+    fn from_value(value: &Value) -> anyhow::Result<Self> {
+        let item = match value {
+            Value::Compound(item) => item,
+            _ => anyhow::bail!(
+                "Malformed inventory found. {:?} is not an item compound",
+                value
+            ),
+        };
+        let slot = match item.get("Slot") {
+            Some(Value::Byte(slot)) => *slot,
+            _ => anyhow::bail!("Malformed item found. No Slot byte"),
+        };
+        let id = Identifier::from(string(item.get("id")).context("Malformed item found. No id")?);
+        let optional_compound = |name: &str| match item.get(name) {
+            None => Ok(None),
+            Some(Value::Compound(fields)) => Ok(Some(fields.clone())),
+            Some(_) => anyhow::bail!("Malformed item {} found. {} is not a compound", id, name),
+        };
+        let (count, data) = match (item.get("Count"), item.get("count")) {
+            (Some(Value::Byte(count)), None) if !item.contains_key("components") => {
+                (i32::from(*count), ItemData::Tag(optional_compound("tag")?))
+            }
+            (None, Some(Value::Int(count))) if !item.contains_key("tag") => (
+                *count,
+                ItemData::Components(optional_compound("components")?),
+            ),
+            _ => anyhow::bail!(
+                "Malformed item {} found. Neither a Count byte nor a count int",
+                id
+            ),
+        };
+        Ok(SavedItem {
+            slot,
+            id,
+            count,
+            data,
+        })
+    }
+}
+
+/// A player's `playerdata/<uuid>.dat`, or the `Player` compound of a singleplayer level.dat.
+pub struct PlayerView<'a>(Fields<'a>);
+
+impl<'a> PlayerView<'a> {
+    pub fn new(blob: &'a Blob) -> Self {
+        PlayerView(Fields::Root(blob))
+    }
+
+    pub fn from_compound(compound: &'a Map<String, Value>) -> Self {
+        PlayerView(Fields::Compound(compound))
+    }
+
+    pub fn data_version(&self) -> Option<i32> {
+        int(self.0.get("DataVersion"))
+    }
+
+    /// Read from the `UUID` int array, or the `UUIDMost` and `UUIDLeast` longs before 1.16.
+    pub fn uuid(&self) -> Option<Uuid> {
+        match (
+            self.0.get("UUID"),
+            self.0.get("UUIDMost"),
+            self.0.get("UUIDLeast"),
+        ) {
+            (Some(Value::IntArray(ints)), _, _) if ints.len() == 4 => {
+                let mut bytes = [0u8; 16];
+                for (chunk, int) in bytes.chunks_exact_mut(4).zip(ints) {
+                    chunk.copy_from_slice(&int.to_be_bytes());
+                }
+                Some(Uuid::from_bytes(bytes))
+            }
+            (_, Some(Value::Long(most)), Some(Value::Long(least))) => Some(Uuid::from_u128(
+                (*most as u64 as u128) << 64 | *least as u64 as u128,
+            )),
+            _ => None,
+        }
+    }
+
+    /// Since 1.16, before which dimensions were numbered.
+    pub fn dimension(&self) -> Option<Identifier> {
+        string(self.0.get("Dimension")).map(Identifier::from)
+    }
+
+    pub fn position(&self) -> Option<(f64, f64, f64)> {
+        match list(self.0.get("Pos")) {
+            [Value::Double(x), Value::Double(y), Value::Double(z)] => Some((*x, *y, *z)),
+            _ => None,
+        }
+    }
+
+    /// The yaw and pitch in degrees.
+    pub fn rotation(&self) -> Option<(f32, f32)> {
+        match list(self.0.get("Rotation")) {
+            [Value::Float(yaw), Value::Float(pitch)] => Some((*yaw, *pitch)),
+            _ => None,
+        }
+    }
+
+    pub fn selected_slot(&self) -> Option<i32> {
+        int(self.0.get("SelectedItemSlot"))
+    }
+
+    /// The main inventory, hotbar, armor and offhand.
+    pub fn inventory(&self) -> anyhow::Result<Vec<SavedItem>> {
+        list(self.0.get("Inventory"))
+            .iter()
+            .map(SavedItem::from_value)
+            .collect()
+    }
+
+    pub fn ender_items(&self) -> anyhow::Result<Vec<SavedItem>> {
+        list(self.0.get("EnderItems"))
+            .iter()
+            .map(SavedItem::from_value)
+            .collect()
+    }
+}
@@ -1,5 +1,23 @@
+pub mod chunk;
+pub mod collections;
 pub mod encoder;
 pub mod ext;
+pub mod fixed;
+pub mod legacy;
+#[cfg(feature = "level")]
+pub mod level;
+pub mod light;
+pub mod limits;
 pub mod nums;
+pub mod optional;
 pub mod strings;
 pub mod common;
+#[cfg(feature = "codec")]
+pub mod codec;
+pub mod packet;
+pub mod packets;
+pub mod ping;
+#[cfg(feature = "region")]
+pub mod region;
+pub mod registry;
+pub mod state;
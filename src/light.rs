@@ -0,0 +1,427 @@
+use crate::collections::LengthPrefix;
+use crate::encoder::*;
+use crate::limits::DecodeLimits;
+use crate::nums::VarInt;
+use anyhow::Context;
+use std::io::{Read, Write};
+use tokio::io::AsyncWrite;
+
+/// A Java `BitSet`, sent as its longs with bit `i` in long `i / 64`.
+///
+/// Trailing zero longs are trimmed as `BitSet::toLongArray` does, so equal sets compare equal.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct BitSet(Vec<i64>);
+
+impl BitSet {
+    pub fn new() -> Self {
+        BitSet(Vec::new())
+    }
+
+    pub fn from_longs(mut longs: Vec<i64>) -> Self {
+        while longs.last() == Some(&0) {
+            longs.pop();
+        }
+        BitSet(longs)
+    }
+
+    pub fn longs(&self) -> &[i64] {
+        &self.0
+    }
+
+    pub fn get(&self, index: usize) -> bool {
+        self.0
+            .get(index / 64)
+            .is_some_and(|long| long >> (index % 64) & 1 == 1)
+    }
+
+    pub fn set(&mut self, index: usize, value: bool) {
+        let long = index / 64;
+        if value {
+            if long >= self.0.len() {
+                self.0.resize(long + 1, 0);
+            }
+            self.0[long] |= 1 << (index % 64);
+        } else if long < self.0.len() {
+            self.0[long] &= !(1 << (index % 64));
+            while self.0.last() == Some(&0) {
+                self.0.pop();
+            }
+        }
+    }
+
+    /// How many bits are set.
+    pub fn count_ones(&self) -> usize {
+        self.0.iter().map(|long| long.count_ones() as usize).sum()
+    }
+
+    /// How many set bits come before `index`.
+    fn rank(&self, index: usize) -> usize {
+        let long = index / 64;
+        let below: usize = self
+            .0
+            .iter()
+            .take(long)
+            .map(|long| long.count_ones() as usize)
+            .sum();
+        let partial = match self.0.get(long) {
+            Some(bits) => (*bits as u64 & ((1u64 << (index % 64)) - 1)).count_ones() as usize,
+            None => 0,
+        };
+        below + partial
+    }
+}
+
+impl Decodable for BitSet {
+    fn decode<R: Read>(reader: &mut R) -> anyhow::Result<Self> {
+        Self::decode_limited(reader, &mut DecodeLimits::default())
+    }
+
+    fn decode_buf<B: bytes::Buf>(buf: &mut B) -> anyhow::Result<Self> {
+        Self::decode_buf_limited(buf, &mut DecodeLimits::default())
+    }
+
+    fn decode_buf_limited<B: bytes::Buf>(
+        buf: &mut B,
+        limits: &mut DecodeLimits,
+    ) -> anyhow::Result<Self> {
+        let length = VarInt::decode_buf(buf)?.to_length()?;
+        Ok(BitSet::from_longs(i64::decode_many_buf_limited(
+            buf, length, limits,
+        )?))
+    }
+
+    fn decode_limited<R: Read>(reader: &mut R, limits: &mut DecodeLimits) -> anyhow::Result<Self> {
+        let length = VarInt::decode(reader)?.to_length()?;
+        Ok(BitSet::from_longs(i64::decode_many(
+            reader, length, limits,
+        )?))
+    }
+}
+
+impl Encodable for BitSet {
+    fn encode<W: Write>(&self, writer: &mut W) -> anyhow::Result<()> {
+        VarInt::from_length(self.0.len())?.encode(writer)?;
+        i64::encode_many(&self.0, writer)
+    }
+
+    fn size(&self) -> anyhow::Result<VarInt> {
+        VarInt::from_length(self.0.len())?
+            .size()?
+            .try_add(i64::size_many(&self.0)?)
+    }
+
+    fn encode_buf<B: bytes::BufMut>(&self, buf: &mut B) -> anyhow::Result<()> {
+        VarInt::from_length(self.0.len())?.encode_buf(buf)?;
+        i64::encode_many_buf(&self.0, buf)
+    }
+}
+
+#[async_trait::async_trait]
+impl AsyncEncodable for BitSet {
+    async fn async_encode<W: AsyncWrite + Send + Unpin>(
+        &self,
+        writer: &mut W,
+    ) -> anyhow::Result<()> {
+        VarInt::from_length(self.0.len())?
+            .async_encode(writer)
+            .await?;
+        i64::async_encode_many(&self.0, writer).await
+    }
+}
+
+/// The light levels of one 16x16x16 section, two to a byte with the even index in the low nibble.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct NibbleArray(pub Box<[u8; NibbleArray::BYTES]>);
+
+impl Default for NibbleArray {
+    fn default() -> Self {
+        NibbleArray(Box::new([0; NibbleArray::BYTES]))
+    }
+}
+
+impl NibbleArray {
+    pub const BYTES: usize = 2048;
+
+    /// Every level set to `level`.
+    pub fn filled(level: u8) -> Self {
+        let level = level & 15;
+        NibbleArray(Box::new([level << 4 | level; NibbleArray::BYTES]))
+    }
+
+    /// The level at a position within the section, each coordinate taken modulo 16.
+    pub fn get(&self, x: i32, y: i32, z: i32) -> u8 {
+        let index = Self::index(x, y, z);
+        self.0[index >> 1] >> ((index & 1) * 4) & 15
+    }
+
+    pub fn set(&mut self, x: i32, y: i32, z: i32, level: u8) {
+        let index = Self::index(x, y, z);
+        let shift = (index & 1) * 4;
+        let byte = &mut self.0[index >> 1];
+        *byte = (*byte & !(15 << shift)) | (level & 15) << shift;
+    }
+
+    /// Whether every level is 0, which is sent through the empty mask instead.
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().all(|byte| *byte == 0)
+    }
+
+    fn index(x: i32, y: i32, z: i32) -> usize {
+        (((y & 15) << 8) | ((z & 15) << 4) | (x & 15)) as usize
+    }
+
+    fn check_length(length: VarInt) -> anyhow::Result<()> {
+        if *length != NibbleArray::BYTES as i32 {
+            anyhow::bail!(
+                "Malformed light array found. Length {}, expected {}",
+                length,
+                NibbleArray::BYTES
+            );
+        }
+        Ok(())
+    }
+}
+
+impl Decodable for NibbleArray {
+    fn decode<R: Read>(reader: &mut R) -> anyhow::Result<Self> {
+        Self::check_length(VarInt::decode(reader)?)?;
+        let mut array = NibbleArray::default();
+        reader
+            .read_exact(&mut array.0[..])
+            .context("Unexpected EOF while reading light array from buffer.")?;
+        Ok(array)
+    }
+
+    fn decode_buf<B: bytes::Buf>(buf: &mut B) -> anyhow::Result<Self> {
+        Self::check_length(VarInt::decode_buf(buf)?)?;
+        check_remaining(buf, NibbleArray::BYTES, "light array")?;
+        let mut array = NibbleArray::default();
+        buf.copy_to_slice(&mut array.0[..]);
+        Ok(array)
+    }
+
+    fn decode_limited<R: Read>(reader: &mut R, limits: &mut DecodeLimits) -> anyhow::Result<Self> {
+        limits.allocate(NibbleArray::BYTES)?;
+        Self::decode(reader)
+    }
+
+    fn decode_buf_limited<B: bytes::Buf>(
+        buf: &mut B,
+        limits: &mut DecodeLimits,
+    ) -> anyhow::Result<Self> {
+        limits.allocate(NibbleArray::BYTES)?;
+        Self::decode_buf(buf)
+    }
+}
+
+impl Encodable for NibbleArray {
+    fn encode<W: Write>(&self, writer: &mut W) -> anyhow::Result<()> {
+        VarInt::from(NibbleArray::BYTES as i32).encode(writer)?;
+        writer
+            .write_all(&self.0[..])
+            .context("Failed to write light array into buffer.")
+    }
+
+    fn size(&self) -> anyhow::Result<VarInt> {
+        Ok(VarInt::from(2 + NibbleArray::BYTES as i32))
+    }
+
+    fn encode_buf<B: bytes::BufMut>(&self, buf: &mut B) -> anyhow::Result<()> {
+        VarInt::from(NibbleArray::BYTES as i32).encode_buf(buf)?;
+        check_remaining_mut(buf, NibbleArray::BYTES, "light array")?;
+        buf.put_slice(&self.0[..]);
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl AsyncEncodable for NibbleArray {
+    async fn async_encode<W: AsyncWrite + Send + Unpin>(
+        &self,
+        writer: &mut W,
+    ) -> anyhow::Result<()> {
+        use tokio::io::AsyncWriteExt;
+
+        VarInt::from(NibbleArray::BYTES as i32)
+            .async_encode(writer)
+            .await?;
+        writer
+            .write_all(&self.0[..])
+            .await
+            .context("Failed to write light array into buffer.")
+    }
+}
+
+/// The light masks and arrays of one light type.
+///
+/// A set bit in `mask` means an array is sent, a set bit in `empty_mask` that the section is
+/// dark. With neither set the client keeps whatever light it had.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct LightLayer {
+    pub mask: BitSet,
+    pub empty_mask: BitSet,
+    /// One per bit of `mask`, in order.
+    pub arrays: Vec<NibbleArray>,
+}
+
+impl LightLayer {
+    pub fn get(&self, section: usize) -> Option<&NibbleArray> {
+        if self.mask.get(section) {
+            self.arrays.get(self.mask.rank(section))
+        } else {
+            None
+        }
+    }
+
+    /// Sends `light` for a section, through the empty mask when it is all 0 as vanilla does.
+    pub fn set(&mut self, section: usize, light: NibbleArray) {
+        self.remove(section);
+        if light.is_empty() {
+            self.empty_mask.set(section, true);
+        } else {
+            self.arrays.insert(self.mask.rank(section), light);
+            self.mask.set(section, true);
+        }
+    }
+
+    /// Stops sending light for a section, so the client keeps its own.
+    pub fn remove(&mut self, section: usize) -> Option<NibbleArray> {
+        self.empty_mask.set(section, false);
+        if self.mask.get(section) {
+            let array = self.arrays.remove(self.mask.rank(section));
+            self.mask.set(section, false);
+            Some(array)
+        } else {
+            None
+        }
+    }
+
+    fn check_arrays(&self) -> anyhow::Result<()> {
+        if self.arrays.len() != self.mask.count_ones() {
+            anyhow::bail!(
+                "Malformed light data found. {} arrays for {} sections",
+                self.arrays.len(),
+                self.mask.count_ones()
+            );
+        }
+        Ok(())
+    }
+}
+
+/// The light half of Chunk Data and Update Light, as sent since 1.20.
+///
+/// Light sections start one below the world and end one above it, so bit 0 of each mask is the
+/// section under the lowest chunk section. Chunk Data and Update Light is a `ChunkColumn`
+/// followed by `LightData`, and Update Light decodes as `(VarInt, VarInt, LightData)`.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct LightData {
+    pub sky: LightLayer,
+    pub block: LightLayer,
+}
+
+impl LightData {
+    fn from_parts(
+        masks: (BitSet, BitSet, BitSet, BitSet),
+        sky: Vec<NibbleArray>,
+        block: Vec<NibbleArray>,
+    ) -> anyhow::Result<Self> {
+        let (sky_mask, block_mask, empty_sky_mask, empty_block_mask) = masks;
+        let light = LightData {
+            sky: LightLayer {
+                mask: sky_mask,
+                empty_mask: empty_sky_mask,
+                arrays: sky,
+            },
+            block: LightLayer {
+                mask: block_mask,
+                empty_mask: empty_block_mask,
+                arrays: block,
+            },
+        };
+        light.sky.check_arrays()?;
+        light.block.check_arrays()?;
+        Ok(light)
+    }
+}
+
+impl Decodable for LightData {
+    fn decode<R: Read>(reader: &mut R) -> anyhow::Result<Self> {
+        Self::decode_limited(reader, &mut DecodeLimits::default())
+    }
+
+    fn decode_buf<B: bytes::Buf>(buf: &mut B) -> anyhow::Result<Self> {
+        Self::decode_buf_limited(buf, &mut DecodeLimits::default())
+    }
+
+    fn decode_buf_limited<B: bytes::Buf>(
+        buf: &mut B,
+        limits: &mut DecodeLimits,
+    ) -> anyhow::Result<Self> {
+        let masks = Decodable::decode_buf_limited(buf, limits)?;
+        let length = VarInt::decode_buf(buf)?.to_length()?;
+        let sky = NibbleArray::decode_many_buf_limited(buf, length, limits)?;
+        let length = VarInt::decode_buf(buf)?.to_length()?;
+        let block = NibbleArray::decode_many_buf_limited(buf, length, limits)?;
+        Self::from_parts(masks, sky, block)
+    }
+
+    fn decode_limited<R: Read>(reader: &mut R, limits: &mut DecodeLimits) -> anyhow::Result<Self> {
+        let masks = Decodable::decode_limited(reader, limits)?;
+        let length = VarInt::decode(reader)?.to_length()?;
+        let sky = NibbleArray::decode_many(reader, length, limits)?;
+        let length = VarInt::decode(reader)?.to_length()?;
+        let block = NibbleArray::decode_many(reader, length, limits)?;
+        Self::from_parts(masks, sky, block)
+    }
+}
+
+impl Encodable for LightData {
+    fn encode<W: Write>(&self, writer: &mut W) -> anyhow::Result<()> {
+        self.sky.check_arrays()?;
+        self.block.check_arrays()?;
+        self.sky.mask.encode(writer)?;
+        self.block.mask.encode(writer)?;
+        self.sky.empty_mask.encode(writer)?;
+        self.block.empty_mask.encode(writer)?;
+        VarInt::from_length(self.sky.arrays.len())?.encode(writer)?;
+        NibbleArray::encode_many(&self.sky.arrays, writer)?;
+        VarInt::from_length(self.block.arrays.len())?.encode(writer)?;
+        NibbleArray::encode_many(&self.block.arrays, writer)
+    }
+
+    fn size(&self) -> anyhow::Result<VarInt> {
+        self.sky
+            .mask
+            .size()?
+            .try_add(self.block.mask.size()?)?
+            .try_add(self.sky.empty_mask.size()?)?
+            .try_add(self.block.empty_mask.size()?)?
+            .try_add(VarInt::from_length(self.sky.arrays.len())?.size()?)?
+            .try_add(NibbleArray::size_many(&self.sky.arrays)?)?
+            .try_add(VarInt::from_length(self.block.arrays.len())?.size()?)?
+            .try_add(NibbleArray::size_many(&self.block.arrays)?)
+    }
+}
+
+#[async_trait::async_trait]
+impl AsyncEncodable for LightData {
+    async fn async_encode<W: AsyncWrite + Send + Unpin>(
+        &self,
+        writer: &mut W,
+    ) -> anyhow::Result<()> {
+        self.sky.check_arrays()?;
+        self.block.check_arrays()?;
+        self.sky.mask.async_encode(writer).await?;
+        self.block.mask.async_encode(writer).await?;
+        self.sky.empty_mask.async_encode(writer).await?;
+        self.block.empty_mask.async_encode(writer).await?;
+        VarInt::from_length(self.sky.arrays.len())?
+            .async_encode(writer)
+            .await?;
+        NibbleArray::async_encode_many(&self.sky.arrays, writer).await?;
+        VarInt::from_length(self.block.arrays.len())?
+            .async_encode(writer)
+            .await?;
+        NibbleArray::async_encode_many(&self.block.arrays, writer).await
+    }
+}
@@ -0,0 +1,144 @@
+use std::fmt;
+
+/// Caps on what decoding bytes from an untrusted peer may allocate.
+///
+/// Limits are threaded through `Decodable::decode_limited` and `decode_buf_limited`, which charge
+/// every allocation sized by the wire against `max_packet_allocation`. `RawPacket::decode_packet_limited` starts that
+/// budget afresh for each packet.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DecodeLimits {
+    pub max_collection_length: usize,
+    pub max_string_bytes: usize,
+    pub max_nbt_depth: usize,
+    pub max_nbt_bytes: usize,
+    pub max_packet_allocation: usize,
+    allocated: usize,
+}
+
+impl Default for DecodeLimits {
+    /// Vanilla's own bounds: a Chat string, 512 levels and 2 MiB of NBT, and twice the largest
+    /// uncompressed packet.
+    fn default() -> Self {
+        DecodeLimits {
+            max_collection_length: 2097152,
+            max_string_bytes: 1048576,
+            max_nbt_depth: 512,
+            max_nbt_bytes: 2097152,
+            max_packet_allocation: 16777216,
+            allocated: 0,
+        }
+    }
+}
+
+impl DecodeLimits {
+    pub fn new() -> Self {
+        DecodeLimits::default()
+    }
+
+    /// Bytes charged against the packet budget so far.
+    pub fn allocated(&self) -> usize {
+        self.allocated
+    }
+
+    pub fn reset(&mut self) {
+        self.allocated = 0;
+    }
+
+    /// Charges `bytes` against the packet budget.
+    pub fn allocate(&mut self, bytes: usize) -> Result<(), LimitError> {
+        let remaining = self.max_packet_allocation.saturating_sub(self.allocated);
+        if bytes > remaining {
+            return Err(LimitError::PacketAllocation {
+                requested: bytes,
+                remaining,
+            });
+        }
+        self.allocated += bytes;
+        Ok(())
+    }
+
+    /// Checks a declared collection length and charges its items up front, so a forged length
+    /// fails before anything is decoded.
+    pub fn collection(&mut self, length: usize, item_size: usize) -> Result<(), LimitError> {
+        if length > self.max_collection_length {
+            return Err(LimitError::CollectionLength {
+                length,
+                limit: self.max_collection_length,
+            });
+        }
+        self.allocate(length.saturating_mul(item_size))
+    }
+
+    pub fn string(&mut self, bytes: usize) -> Result<(), LimitError> {
+        if bytes > self.max_string_bytes {
+            return Err(LimitError::StringBytes {
+                length: bytes,
+                limit: self.max_string_bytes,
+            });
+        }
+        self.allocate(bytes)
+    }
+
+    pub fn nbt_depth(&self, depth: usize) -> Result<(), LimitError> {
+        if depth > self.max_nbt_depth {
+            return Err(LimitError::NbtDepth {
+                limit: self.max_nbt_depth,
+            });
+        }
+        Ok(())
+    }
+
+    /// Checks an NBT value will still fit once `bytes` more are read, then charges them.
+    pub fn nbt_bytes(&mut self, read: usize, bytes: usize) -> Result<(), LimitError> {
+        if bytes > self.max_nbt_bytes.saturating_sub(read) {
+            return Err(LimitError::NbtBytes {
+                limit: self.max_nbt_bytes,
+            });
+        }
+        self.allocate(bytes)
+    }
+}
+
+/// A decode stopped by `DecodeLimits`, distinguishable from malformed input by downcasting the
+/// `anyhow::Error`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LimitError {
+    CollectionLength { length: usize, limit: usize },
+    StringBytes { length: usize, limit: usize },
+    NbtDepth { limit: usize },
+    NbtBytes { limit: usize },
+    PacketAllocation { requested: usize, remaining: usize },
+}
+
+impl fmt::Display for LimitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LimitError::CollectionLength { length, limit } => write!(
+                f,
+                "Collection length {} exceeds the limit of {}.",
+                length, limit
+            ),
+            LimitError::StringBytes { length, limit } => write!(
+                f,
+                "String of {} bytes exceeds the limit of {}.",
+                length, limit
+            ),
+            LimitError::NbtDepth { limit } => {
+                write!(f, "Failed to decode nbt nested deeper than {}.", limit)
+            }
+            LimitError::NbtBytes { limit } => {
+                write!(f, "Failed to decode nbt larger than {} bytes.", limit)
+            }
+            LimitError::PacketAllocation {
+                requested,
+                remaining,
+            } => write!(
+                f,
+                "Allocation of {} bytes exceeds the {} left in the packet budget.",
+                requested, remaining
+            ),
+        }
+    }
+}
+
+impl std::error::Error for LimitError {}
@@ -10,6 +10,44 @@ macro_rules! declare_primitives {
                     reader.read_exact(&mut into).context(format!("Unexpected EOF while reading {} from buffer.", stringify!($prim)))?;
                     Ok(<$prim>::from_be_bytes(into))
                 }
+
+                fn decode_buf<B: bytes::Buf>(buf: &mut B) -> anyhow::Result<Self> {
+                    $crate::encoder::check_remaining(buf, $size, stringify!($prim))?;
+                    let mut into = [0u8; $size];
+                    buf.copy_to_slice(&mut into);
+                    Ok(<$prim>::from_be_bytes(into))
+                }
+
+                fn decode_many<R: std::io::Read>(
+                    reader: &mut R,
+                    count: usize,
+                    limits: &mut $crate::limits::DecodeLimits,
+                ) -> anyhow::Result<Vec<Self>> {
+                    use std::io::Read;
+
+                    limits.collection(count, $size)?;
+                    let length = count.checked_mul($size).with_context(|| format!("Failed to read {} {} from buffer.", count, stringify!($prim)))?;
+                    // grows past the first chunk with the bytes actually read rather than trusting the count
+                    let mut bytes = Vec::with_capacity(length.min(65536));
+                    reader.take(length as u64).read_to_end(&mut bytes)?;
+                    if bytes.len() != length {
+                        anyhow::bail!("Unexpected EOF while reading {} {} from buffer.", count, stringify!($prim));
+                    }
+                    Ok(from_be_chunks!(bytes, $prim, $size))
+                }
+
+                fn decode_many_buf_limited<B: bytes::Buf>(
+                    buf: &mut B,
+                    count: usize,
+                    limits: &mut $crate::limits::DecodeLimits,
+                ) -> anyhow::Result<Vec<Self>> {
+                    limits.collection(count, $size)?;
+                    let length = count.checked_mul($size).with_context(|| format!("Failed to read {} {} from buffer.", count, stringify!($prim)))?;
+                    $crate::encoder::check_remaining(buf, length, stringify!($prim))?;
+                    let mut bytes = vec![0u8; length];
+                    buf.copy_to_slice(&mut bytes);
+                    Ok(from_be_chunks!(bytes, $prim, $size))
+                }
             }
 
             impl $crate::encoder::Encodable for $prim {
@@ -20,6 +58,26 @@ macro_rules! declare_primitives {
                 fn size(&self) -> anyhow::Result<$crate::nums::VarInt> {
                     Ok($crate::nums::VarInt::from($size))
                 }
+
+                fn encode_buf<B: bytes::BufMut>(&self, buf: &mut B) -> anyhow::Result<()> {
+                    $crate::encoder::check_remaining_mut(buf, $size, stringify!($prim))?;
+                    buf.put_slice(&self.to_be_bytes());
+                    Ok(())
+                }
+
+                fn encode_many<W: std::io::Write>(items: &[Self], writer: &mut W) -> anyhow::Result<()> {
+                    writer.write_all(&to_be_chunks!(items, $size)).with_context(|| format!("Failed to write {} {} into buffer.", items.len(), stringify!($prim)))
+                }
+
+                fn size_many(items: &[Self]) -> anyhow::Result<$crate::nums::VarInt> {
+                    items.len().checked_mul($size).and_then(|size| $crate::nums::VarInt::try_from(size).ok()).with_context(|| format!("Failed to size {} {}.", items.len(), stringify!($prim)))
+                }
+
+                fn encode_many_buf<B: bytes::BufMut>(items: &[Self], buf: &mut B) -> anyhow::Result<()> {
+                    $crate::encoder::check_remaining_mut(buf, items.len().saturating_mul($size), stringify!($prim))?;
+                    buf.put_slice(&to_be_chunks!(items, $size));
+                    Ok(())
+                }
             }
 
             #[async_trait::async_trait]
@@ -34,11 +92,45 @@ macro_rules! declare_primitives {
                         .await
                         .context(format!("Failed to write {} into buffer.", &self))
                 }
+
+                async fn async_encode_many<W: tokio::io::AsyncWrite + Send + Unpin>(
+                    items: &[Self],
+                    writer: &mut W,
+                ) -> anyhow::Result<()> {
+                    use tokio::io::AsyncWriteExt;
+                    writer
+                        .write_all(&to_be_chunks!(items, $size))
+                        .await
+                        .with_context(|| format!("Failed to write {} {} into buffer.", items.len(), stringify!($prim)))
+                }
             }
         )*
     }
 }
 
+macro_rules! from_be_chunks {
+    ($bytes:expr, $prim:ty, $size:literal) => {
+        $bytes
+            .chunks_exact($size)
+            .map(|chunk| {
+                let mut into = [0u8; $size];
+                into.copy_from_slice(chunk);
+                <$prim>::from_be_bytes(into)
+            })
+            .collect()
+    };
+}
+
+macro_rules! to_be_chunks {
+    ($items:expr, $size:literal) => {{
+        let mut bytes = vec![0u8; $items.len() * $size];
+        for (chunk, item) in bytes.chunks_exact_mut($size).zip($items) {
+            chunk.copy_from_slice(&item.to_be_bytes());
+        }
+        bytes
+    }};
+}
+
 macro_rules! impl_into_num_bind {
     ($name:ident, $sim:ty, $prim:ty, $relationship:ident) => {
         impl From<$prim> for $name {
@@ -202,8 +294,52 @@ macro_rules! impl_variable_number_bind {
     }
 }
 
+macro_rules! checked_operators {
+    ($name:ident, $($checked:ident, $saturating:ident, $wrapping:ident;)*) => {
+        $(
+            pub fn $checked(self, rhs: Self) -> Option<Self> {
+                self.0.$checked(rhs.0).map($name)
+            }
+
+            pub fn $saturating(self, rhs: Self) -> Self {
+                $name(self.0.$saturating(rhs.0))
+            }
+
+            pub fn $wrapping(self, rhs: Self) -> Self {
+                $name(self.0.$wrapping(rhs.0))
+            }
+        )*
+    };
+}
+
+macro_rules! wire_mapping {
+    (plain, $primitive_signed:ty, $primitive_unsigned:ty) => {
+        #[inline(always)]
+        fn wire(&self) -> $primitive_unsigned {
+            self.0 as $primitive_unsigned
+        }
+
+        #[inline(always)]
+        fn from_wire(wire: $primitive_unsigned) -> $primitive_signed {
+            wire as $primitive_signed
+        }
+    };
+    // interleaves signs so small negative numbers stay short: 0, -1, 1, -2 are sent as 0, 1, 2, 3
+    (zigzag, $primitive_signed:ty, $primitive_unsigned:ty) => {
+        #[inline(always)]
+        fn wire(&self) -> $primitive_unsigned {
+            ((self.0 << 1) ^ (self.0 >> (<$primitive_signed>::BITS - 1))) as $primitive_unsigned
+        }
+
+        #[inline(always)]
+        fn from_wire(wire: $primitive_unsigned) -> $primitive_signed {
+            (wire >> 1) as $primitive_signed ^ -((wire & 1) as $primitive_signed)
+        }
+    };
+}
+
 macro_rules! declare_variable_number {
-    ($name:ident, $primitive_signed:ty, $bit_limit:literal, $primitive_unsigned:ty, $and_check:literal $(,
+    ($name:ident, $primitive_signed:ty, $bit_limit:literal, $primitive_unsigned:ty, $wire:ident $(,
         $prim:ty: ($relationship:ident $(|$rel_err:ty)?, $alt_relationship:ident $(|$alt_err:ty)?)
     )*) => {
         #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Debug)]
@@ -220,6 +356,76 @@ macro_rules! declare_variable_number {
         }
 
         impl $name {
+            /// The most bytes an encoded value can take.
+            pub const MAX_SIZE: usize = $bit_limit / 7;
+
+            wire_mapping!($wire, $primitive_signed, $primitive_unsigned);
+
+            /// The encoded length, taken from the leading zeros rather than by encoding.
+            /// `(highest_bit * 9 + 73) / 64` rounds up to 7-bit groups without a division.
+            #[inline]
+            pub fn encoded_len(&self) -> usize {
+                let highest_bit = <$primitive_unsigned>::BITS - 1
+                    - (self.wire() | 1).leading_zeros();
+                (highest_bit as usize * 9 + 73) / 64
+            }
+
+            /// Encodes onto the stack, returning the bytes and how many of them are used. The
+            /// groups are assembled in a register, so the bytes are stored with one write.
+            #[inline]
+            pub fn encode_array(&self) -> ([u8; 16], usize) {
+                let length = self.encoded_len();
+                let value = self.wire();
+                let mut word = 0u128;
+                for index in 0..Self::MAX_SIZE {
+                    word |= u128::from(value >> (index * 7) & 0x7F | 0x80) << (index * 8);
+                }
+                word &= !(0x80u128 << ((length - 1) * 8));
+                (word.to_le_bytes(), length)
+            }
+
+            /// Decodes from the front of `bytes`, returning the value and how many bytes it took.
+            #[inline]
+            pub fn decode_slice(bytes: &[u8]) -> anyhow::Result<(Self, usize)> {
+                match Self::decode_prefix(bytes) {
+                    Some(decoded) => Ok(decoded),
+                    None => Err(Self::slice_error(bytes)),
+                }
+            }
+
+            /// With `MAX_SIZE` bytes available the inlined loop has a fixed trip count and no
+            /// bounds checks, so it is fully unrolled.
+            #[inline(always)]
+            fn decode_prefix(bytes: &[u8]) -> Option<(Self, usize)> {
+                match bytes.get(..Self::MAX_SIZE) {
+                    Some(bytes) => Self::decode_groups(bytes),
+                    None => Self::decode_groups(bytes),
+                }
+            }
+
+            #[inline(always)]
+            fn decode_groups(bytes: &[u8]) -> Option<(Self, usize)> {
+                let mut value: $primitive_unsigned = 0;
+                for (index, byte) in bytes.iter().enumerate() {
+                    value |= <$primitive_unsigned>::from(byte & 0x7F) << (index * 7);
+                    if byte & 0x80 == 0 {
+                        return Some(($name(Self::from_wire(value)), index + 1));
+                    }
+                }
+                None
+            }
+
+            #[cold]
+            fn slice_error(bytes: &[u8]) -> anyhow::Error {
+                if bytes.iter().take(Self::MAX_SIZE).all(|byte| byte & 0x80 != 0)
+                    && bytes.len() < Self::MAX_SIZE
+                {
+                    anyhow::anyhow!("Unexpected EOF while decoding {}.", stringify!($name))
+                } else {
+                    anyhow::anyhow!("Failed to decode {}, too many bytes.", stringify!($name))
+                }
+            }
+
             pub fn decode_and_size(reader: &mut impl std::io::Read) -> anyhow::Result<(VarInt, Self)> {
                 let mut running_size = 0;
                 let mut value: $primitive_signed = 0;
@@ -245,7 +451,7 @@ macro_rules! declare_variable_number {
                         break;
                     }
                 }
-                Ok((VarInt::try_from(running_size)?, $name(value)))
+                Ok((VarInt::try_from(running_size)?, $name(Self::from_wire(value as $primitive_unsigned))))
             }
         }
 
@@ -273,34 +479,68 @@ macro_rules! declare_variable_number {
                         break;
                     }
                 }
-                Ok($name(value))
+                Ok($name(Self::from_wire(value as $primitive_unsigned)))
+            }
+
+            fn decode_buf<B: bytes::Buf>(buf: &mut B) -> anyhow::Result<Self> {
+                let chunk = buf.chunk();
+                if chunk.len() >= Self::MAX_SIZE || chunk.len() == buf.remaining() {
+                    let (value, length) = Self::decode_slice(chunk)?;
+                    buf.advance(length);
+                    return Ok(value);
+                }
+
+                let mut value: $primitive_signed = 0;
+                let mut bit_offset = 0u32;
+                loop {
+                    if bit_offset == $bit_limit {
+                        anyhow::bail!(
+                            "Failed to decode {}, too many bytes.",
+                            stringify!($name)
+                        );
+                    }
+
+                    $crate::encoder::check_remaining(buf, 1, stringify!($name))?;
+                    let byte = buf.get_u8();
+                    value |= <$primitive_signed>::from(byte & 0b01111111)
+                        .overflowing_shl(bit_offset)
+                        .0;
+                    bit_offset += 7;
+
+                    if byte & 0b10000000 == 0 {
+                        break;
+                    }
+                }
+                Ok($name(Self::from_wire(value as $primitive_unsigned)))
             }
         }
 
         impl $crate::encoder::Encodable for $name {
             fn encode<W: std::io::Write>(&self, writer: &mut W) -> anyhow::Result<()> {
-                let mut temp = self.0.clone() as $primitive_unsigned;
-                loop {
-                    if temp & $and_check == 0 {
-                        writer.write_all(&[temp as u8])?;
-                        return Ok(());
-                    }
-                    writer.write_all(&[(temp & 0x7F | 0x80) as u8])?;
-                    temp = temp.overflowing_shr(7).0;
+                // a constant length lets the common single byte case skip the memcpy call
+                if self.wire() & !0x7F == 0 {
+                    writer.write_all(&[self.wire() as u8])?;
+                    return Ok(());
                 }
+                let (bytes, length) = self.encode_array();
+                writer.write_all(&bytes[..length])?;
+                Ok(())
             }
 
             fn size(&self) -> anyhow::Result<$crate::nums::VarInt> {
-                let mut running_size: i32 = 0;
-                let mut temp = self.0.clone() as $primitive_unsigned;
-                loop {
-                    if temp & $and_check == 0 {
-                        running_size += 1;
-                        return Ok(running_size.into());
-                    }
-                    running_size += 1;
-                    temp = temp.overflowing_shr(7).0;
+                Ok($crate::nums::VarInt::from(self.encoded_len() as i32))
+            }
+
+            fn encode_buf<B: bytes::BufMut>(&self, buf: &mut B) -> anyhow::Result<()> {
+                if self.wire() & !0x7F == 0 {
+                    $crate::encoder::check_remaining_mut(buf, 1, stringify!($name))?;
+                    buf.put_u8(self.wire() as u8);
+                    return Ok(());
                 }
+                let (bytes, length) = self.encode_array();
+                $crate::encoder::check_remaining_mut(buf, length, stringify!($name))?;
+                buf.put_slice(&bytes[..length]);
+                Ok(())
             }
         }
 
@@ -312,15 +552,9 @@ macro_rules! declare_variable_number {
             ) -> anyhow::Result<()> {
                 use tokio::io::AsyncWriteExt;
 
-                let mut temp = self.0.clone() as $primitive_unsigned;
-                loop {
-                    if temp & $and_check == 0 {
-                        writer.write_u8(temp as u8).await?;
-                        return Ok(());
-                    }
-                    writer.write_u8((temp & 0x7F | 0x80) as u8).await?;
-                    temp = temp.overflowing_shr(7).0;
-                }
+                let (bytes, length) = self.encode_array();
+                writer.write_all(&bytes[..length]).await?;
+                Ok(())
             }
         }
 
@@ -358,6 +592,37 @@ macro_rules! declare_variable_number {
             }
         }
 
+        impl std::ops::Sub for &$name {
+            type Output = $name;
+
+            fn sub(self, rhs: Self) -> Self::Output {
+                $name(self.0 - rhs.0)
+            }
+        }
+
+        impl std::ops::Sub for $name {
+            type Output = Self;
+
+            fn sub(self, rhs: Self) -> Self::Output {
+                $name(self.0 - rhs.0)
+            }
+        }
+
+        impl $name {
+            checked_operators!($name,
+                checked_add, saturating_add, wrapping_add;
+                checked_sub, saturating_sub, wrapping_sub;
+                checked_mul, saturating_mul, wrapping_mul;
+            );
+
+            /// Adds two sizes, failing instead of overflowing.
+            pub fn try_add(self, rhs: Self) -> anyhow::Result<Self> {
+                self.checked_add(rhs).with_context(|| {
+                    format!("Failed to add {} to {}, the sum overflows {}.", rhs, self, stringify!($name))
+                })
+            }
+        }
+
         impl std::ops::Deref for $name {
             type Target = $primitive_signed;
 
@@ -471,6 +736,18 @@ macro_rules! declare_variable_number {
                 self.0 = self.0 + rhs.0;
             }
         }
+
+        impl std::ops::SubAssign for $name {
+            fn sub_assign(&mut self, rhs: Self) {
+                self.0 = self.0 - rhs.0;
+            }
+        }
+
+        impl std::ops::MulAssign for $name {
+            fn mul_assign(&mut self, rhs: Self) {
+                self.0 = self.0 * rhs.0;
+            }
+        }
     };
 }
 
@@ -485,6 +762,14 @@ impl crate::encoder::Decodable for bool {
             anyhow::bail!("Malformed boolean found. Byte {}", byte);
         }
     }
+
+    fn decode_buf<B: bytes::Buf>(buf: &mut B) -> anyhow::Result<Self> {
+        match u8::decode_buf(buf)? {
+            0x0u8 => Ok(false),
+            0x1u8 => Ok(true),
+            byte => anyhow::bail!("Malformed boolean found. Byte {}", byte),
+        }
+    }
 }
 
 impl crate::encoder::Encodable for bool {
@@ -497,6 +782,10 @@ impl crate::encoder::Encodable for bool {
     fn size(&self) -> anyhow::Result<VarInt> {
         Ok(VarInt::from(1))
     }
+
+    fn encode_buf<B: bytes::BufMut>(&self, buf: &mut B) -> anyhow::Result<()> {
+        (*self as u8).encode_buf(buf)
+    }
 }
 
 #[async_trait::async_trait]
@@ -519,12 +808,104 @@ declare_primitives!(
     |i16;2|
     |u16;2|
     |i32;4|
+    |u32;4|
+    |i64;8|
+    |u64;8|
+    |i128;16|
+    |u128;16|
+    |f32;4|
+    |f64;8|
+);
+
+/// A primitive sent little-endian, as the Bedrock and Query protocols do.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Le<T>(pub T);
+
+impl<T> std::ops::Deref for Le<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> std::ops::DerefMut for Le<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T> From<T> for Le<T> {
+    fn from(item: T) -> Self {
+        Le(item)
+    }
+}
+
+macro_rules! declare_little_endian {
+    ($(|$prim:ty;$size:literal|)+) => {
+        $(
+            impl $crate::encoder::Decodable for Le<$prim> {
+                fn decode<R: std::io::Read>(reader: &mut R) -> anyhow::Result<Self> {
+                    let mut into = [0u8; $size];
+                    reader.read_exact(&mut into).context(format!("Unexpected EOF while reading little-endian {} from buffer.", stringify!($prim)))?;
+                    Ok(Le(<$prim>::from_le_bytes(into)))
+                }
+
+                fn decode_buf<B: bytes::Buf>(buf: &mut B) -> anyhow::Result<Self> {
+                    $crate::encoder::check_remaining(buf, $size, stringify!($prim))?;
+                    let mut into = [0u8; $size];
+                    buf.copy_to_slice(&mut into);
+                    Ok(Le(<$prim>::from_le_bytes(into)))
+                }
+            }
+
+            impl $crate::encoder::Encodable for Le<$prim> {
+                fn encode<W: std::io::Write>(&self, writer: &mut W) -> anyhow::Result<()> {
+                    writer.write_all(&self.0.to_le_bytes()).with_context(|| format!("Failed to write little-endian {} into buffer.", self.0))
+                }
+
+                fn size(&self) -> anyhow::Result<$crate::nums::VarInt> {
+                    Ok($crate::nums::VarInt::from($size))
+                }
+
+                fn encode_buf<B: bytes::BufMut>(&self, buf: &mut B) -> anyhow::Result<()> {
+                    $crate::encoder::check_remaining_mut(buf, $size, stringify!($prim))?;
+                    buf.put_slice(&self.0.to_le_bytes());
+                    Ok(())
+                }
+            }
+
+            #[async_trait::async_trait]
+            impl $crate::encoder::AsyncEncodable for Le<$prim> {
+                async fn async_encode<W: tokio::io::AsyncWrite + Send + Unpin>(
+                    &self,
+                    writer: &mut W,
+                ) -> anyhow::Result<()> {
+                    use tokio::io::AsyncWriteExt;
+                    writer
+                        .write_all(&self.0.to_le_bytes())
+                        .await
+                        .with_context(|| format!("Failed to write little-endian {} into buffer.", self.0))
+                }
+            }
+        )*
+    }
+}
+
+declare_little_endian!(
+    |i16;2|
+    |u16;2|
+    |i32;4|
+    |u32;4|
     |i64;8|
+    |u64;8|
+    |i128;16|
+    |u128;16|
     |f32;4|
     |f64;8|
 );
 
-declare_variable_number!(VarInt, i32, 35, u32, 0xFFFFFF80,
+declare_variable_number!(VarInt, i32, 35, u32, plain,
     u8: (from, try_from | std::num::TryFromIntError),
     i8: (from, try_from | std::num::TryFromIntError),
     u16: (from, try_from | std::num::TryFromIntError),
@@ -538,7 +919,35 @@ declare_variable_number!(VarInt, i32, 35, u32, 0xFFFFFF80,
     isize: (try_from | std::num::TryFromIntError, try_from | std::num::TryFromIntError)
 );
 
-declare_variable_number!(VarLong, i64, 70, u64, 0xFFFFFFFFFFFFFF80,
+declare_variable_number!(VarIntZigZag, i32, 35, u32, zigzag,
+    u8: (from, try_from | std::num::TryFromIntError),
+    i8: (from, try_from | std::num::TryFromIntError),
+    u16: (from, try_from | std::num::TryFromIntError),
+    i16: (from, try_from | std::num::TryFromIntError),
+    u32: (try_from | std::num::TryFromIntError, try_from | std::num::TryFromIntError),
+    u64: (try_from | std::num::TryFromIntError, try_from | std::num::TryFromIntError),
+    i64: (try_from | std::num::TryFromIntError, from),
+    u128: (try_from | std::num::TryFromIntError, try_from | std::num::TryFromIntError),
+    i128: (try_from | std::num::TryFromIntError, from),
+    usize: (try_from | std::num::TryFromIntError, try_from | std::num::TryFromIntError),
+    isize: (try_from | std::num::TryFromIntError, try_from | std::num::TryFromIntError)
+);
+
+declare_variable_number!(VarLong, i64, 70, u64, plain,
+    u8: (from, try_from | std::num::TryFromIntError),
+    i8: (from, try_from | std::num::TryFromIntError),
+    u16: (from, try_from | std::num::TryFromIntError),
+    i16: (from, try_from | std::num::TryFromIntError),
+    u32: (from, try_from | std::num::TryFromIntError),
+    i32: (from, try_from | std::num::TryFromIntError),
+    u64: (try_from | std::num::TryFromIntError, try_from | std::num::TryFromIntError),
+    u128: (try_from | std::num::TryFromIntError, try_from | std::num::TryFromIntError),
+    i128: (try_from | std::num::TryFromIntError, from),
+    usize: (try_from | std::num::TryFromIntError, try_from | std::num::TryFromIntError),
+    isize: (try_from | std::num::TryFromIntError, try_from | std::num::TryFromIntError)
+);
+
+declare_variable_number!(VarLongZigZag, i64, 70, u64, zigzag,
     u8: (from, try_from | std::num::TryFromIntError),
     i8: (from, try_from | std::num::TryFromIntError),
     u16: (from, try_from | std::num::TryFromIntError),
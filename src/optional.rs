@@ -0,0 +1,309 @@
+use crate::encoder::*;
+use crate::limits::DecodeLimits;
+use crate::nums::VarInt;
+use std::io::{Read, Write};
+use tokio::io::AsyncWrite;
+
+macro_rules! option_wrapper {
+    ($name:ident) => {
+        impl<T> std::ops::Deref for $name<T> {
+            type Target = Option<T>;
+
+            fn deref(&self) -> &Self::Target {
+                &self.0
+            }
+        }
+
+        impl<T> std::ops::DerefMut for $name<T> {
+            fn deref_mut(&mut self) -> &mut Self::Target {
+                &mut self.0
+            }
+        }
+
+        impl<T> From<Option<T>> for $name<T> {
+            fn from(item: Option<T>) -> Self {
+                $name(item)
+            }
+        }
+
+        impl<T> From<$name<T>> for Option<T> {
+            fn from(wrapper: $name<T>) -> Self {
+                wrapper.0
+            }
+        }
+    };
+}
+
+/// An optional value preceded by a `bool` saying whether it is present.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct BoolPrefixed<T>(pub Option<T>);
+
+option_wrapper!(BoolPrefixed);
+
+impl<T: Decodable> Decodable for BoolPrefixed<T> {
+    fn decode<R: Read>(reader: &mut R) -> anyhow::Result<Self> {
+        if bool::decode(reader)? {
+            Ok(BoolPrefixed(Some(T::decode(reader)?)))
+        } else {
+            Ok(BoolPrefixed(None))
+        }
+    }
+
+    fn decode_buf<B: bytes::Buf>(buf: &mut B) -> anyhow::Result<Self> {
+        if bool::decode_buf(buf)? {
+            Ok(BoolPrefixed(Some(T::decode_buf(buf)?)))
+        } else {
+            Ok(BoolPrefixed(None))
+        }
+    }
+
+    fn decode_limited<R: Read>(reader: &mut R, limits: &mut DecodeLimits) -> anyhow::Result<Self> {
+        if bool::decode(reader)? {
+            Ok(BoolPrefixed(Some(T::decode_limited(reader, limits)?)))
+        } else {
+            Ok(BoolPrefixed(None))
+        }
+    }
+
+    fn decode_buf_limited<B: bytes::Buf>(
+        buf: &mut B,
+        limits: &mut DecodeLimits,
+    ) -> anyhow::Result<Self> {
+        if bool::decode_buf(buf)? {
+            Ok(BoolPrefixed(Some(T::decode_buf_limited(buf, limits)?)))
+        } else {
+            Ok(BoolPrefixed(None))
+        }
+    }
+}
+
+impl<T: Encodable> Encodable for BoolPrefixed<T> {
+    fn encode<W: Write>(&self, writer: &mut W) -> anyhow::Result<()> {
+        self.0.is_some().encode(writer)?;
+        self.0.encode(writer)
+    }
+
+    fn size(&self) -> anyhow::Result<VarInt> {
+        VarInt::from(1).try_add(self.0.size()?)
+    }
+
+    fn encode_buf<B: bytes::BufMut>(&self, buf: &mut B) -> anyhow::Result<()> {
+        self.0.is_some().encode_buf(buf)?;
+        self.0.encode_buf(buf)
+    }
+}
+
+#[async_trait::async_trait]
+impl<T: AsyncEncodable> AsyncEncodable for BoolPrefixed<T> {
+    async fn async_encode<W: AsyncWrite + Send + Unpin>(
+        &self,
+        writer: &mut W,
+    ) -> anyhow::Result<()> {
+        self.0.is_some().async_encode(writer).await?;
+        self.0.async_encode(writer).await
+    }
+}
+
+/// An optional value at the end of a bounded frame, present if any bytes remain.
+///
+/// Only use this last in a packet: over an unbounded reader it waits for the next bytes.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct Trailing<T>(pub Option<T>);
+
+option_wrapper!(Trailing);
+
+/// Reads the first byte of a trailing value, or `None` at the end of the frame.
+fn peek_byte<R: Read>(reader: &mut R) -> anyhow::Result<Option<u8>> {
+    let mut byte = [0u8; 1];
+    loop {
+        match reader.read(&mut byte) {
+            Ok(0) => return Ok(None),
+            Ok(_) => return Ok(Some(byte[0])),
+            Err(error) if error.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(error) => return Err(error.into()),
+        }
+    }
+}
+
+impl<T: Decodable> Decodable for Trailing<T> {
+    fn decode<R: Read>(reader: &mut R) -> anyhow::Result<Self> {
+        match peek_byte(reader)? {
+            Some(byte) => Ok(Trailing(Some(T::decode(&mut (&[byte][..]).chain(reader))?))),
+            None => Ok(Trailing(None)),
+        }
+    }
+
+    fn decode_buf<B: bytes::Buf>(buf: &mut B) -> anyhow::Result<Self> {
+        if buf.has_remaining() {
+            Ok(Trailing(Some(T::decode_buf(buf)?)))
+        } else {
+            Ok(Trailing(None))
+        }
+    }
+
+    fn decode_limited<R: Read>(reader: &mut R, limits: &mut DecodeLimits) -> anyhow::Result<Self> {
+        match peek_byte(reader)? {
+            Some(byte) => Ok(Trailing(Some(T::decode_limited(
+                &mut (&[byte][..]).chain(reader),
+                limits,
+            )?))),
+            None => Ok(Trailing(None)),
+        }
+    }
+
+    fn decode_buf_limited<B: bytes::Buf>(
+        buf: &mut B,
+        limits: &mut DecodeLimits,
+    ) -> anyhow::Result<Self> {
+        if buf.has_remaining() {
+            Ok(Trailing(Some(T::decode_buf_limited(buf, limits)?)))
+        } else {
+            Ok(Trailing(None))
+        }
+    }
+}
+
+impl<T: Encodable> Encodable for Trailing<T> {
+    fn encode<W: Write>(&self, writer: &mut W) -> anyhow::Result<()> {
+        self.0.encode(writer)
+    }
+
+    fn size(&self) -> anyhow::Result<VarInt> {
+        self.0.size()
+    }
+
+    fn encode_buf<B: bytes::BufMut>(&self, buf: &mut B) -> anyhow::Result<()> {
+        self.0.encode_buf(buf)
+    }
+}
+
+#[async_trait::async_trait]
+impl<T: AsyncEncodable> AsyncEncodable for Trailing<T> {
+    async fn async_encode<W: AsyncWrite + Send + Unpin>(
+        &self,
+        writer: &mut W,
+    ) -> anyhow::Result<()> {
+        self.0.async_encode(writer).await
+    }
+}
+
+/// The "ID or X" type: a registry id, or a value sent inline when the registry lacks it.
+///
+/// On the wire a `VarInt` of 0 is followed by the inline value, anything else is the id plus 1.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum IdOrInline<T> {
+    Id(i32),
+    Inline(T),
+}
+
+impl<T> IdOrInline<T> {
+    pub fn id(&self) -> Option<i32> {
+        match self {
+            IdOrInline::Id(id) => Some(*id),
+            IdOrInline::Inline(_) => None,
+        }
+    }
+
+    pub fn inline(&self) -> Option<&T> {
+        match self {
+            IdOrInline::Id(_) => None,
+            IdOrInline::Inline(item) => Some(item),
+        }
+    }
+
+    pub fn into_inline(self) -> Option<T> {
+        match self {
+            IdOrInline::Id(_) => None,
+            IdOrInline::Inline(item) => Some(item),
+        }
+    }
+
+    fn from_prefix(prefix: VarInt) -> anyhow::Result<Option<Self>> {
+        match *prefix {
+            0 => Ok(None),
+            id if id > 0 => Ok(Some(IdOrInline::Id(id - 1))),
+            id => anyhow::bail!("Malformed registry id found. Id {}", id),
+        }
+    }
+
+    fn prefix(&self) -> anyhow::Result<VarInt> {
+        match self {
+            IdOrInline::Inline(_) => Ok(VarInt::from(0)),
+            IdOrInline::Id(id) if (0..i32::MAX).contains(id) => Ok(VarInt::from(id + 1)),
+            IdOrInline::Id(id) => anyhow::bail!("Failed to encode registry id {}.", id),
+        }
+    }
+}
+
+impl<T: Decodable> Decodable for IdOrInline<T> {
+    fn decode<R: Read>(reader: &mut R) -> anyhow::Result<Self> {
+        match Self::from_prefix(VarInt::decode(reader)?)? {
+            Some(id) => Ok(id),
+            None => Ok(IdOrInline::Inline(T::decode(reader)?)),
+        }
+    }
+
+    fn decode_buf<B: bytes::Buf>(buf: &mut B) -> anyhow::Result<Self> {
+        match Self::from_prefix(VarInt::decode_buf(buf)?)? {
+            Some(id) => Ok(id),
+            None => Ok(IdOrInline::Inline(T::decode_buf(buf)?)),
+        }
+    }
+
+    fn decode_limited<R: Read>(reader: &mut R, limits: &mut DecodeLimits) -> anyhow::Result<Self> {
+        match Self::from_prefix(VarInt::decode(reader)?)? {
+            Some(id) => Ok(id),
+            None => Ok(IdOrInline::Inline(T::decode_limited(reader, limits)?)),
+        }
+    }
+
+    fn decode_buf_limited<B: bytes::Buf>(
+        buf: &mut B,
+        limits: &mut DecodeLimits,
+    ) -> anyhow::Result<Self> {
+        match Self::from_prefix(VarInt::decode_buf(buf)?)? {
+            Some(id) => Ok(id),
+            None => Ok(IdOrInline::Inline(T::decode_buf_limited(buf, limits)?)),
+        }
+    }
+}
+
+impl<T: Encodable> Encodable for IdOrInline<T> {
+    fn encode<W: Write>(&self, writer: &mut W) -> anyhow::Result<()> {
+        self.prefix()?.encode(writer)?;
+        match self {
+            IdOrInline::Id(_) => Ok(()),
+            IdOrInline::Inline(item) => item.encode(writer),
+        }
+    }
+
+    fn size(&self) -> anyhow::Result<VarInt> {
+        let size = self.prefix()?.size()?;
+        match self {
+            IdOrInline::Id(_) => Ok(size),
+            IdOrInline::Inline(item) => size.try_add(item.size()?),
+        }
+    }
+
+    fn encode_buf<B: bytes::BufMut>(&self, buf: &mut B) -> anyhow::Result<()> {
+        self.prefix()?.encode_buf(buf)?;
+        match self {
+            IdOrInline::Id(_) => Ok(()),
+            IdOrInline::Inline(item) => item.encode_buf(buf),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<T: AsyncEncodable> AsyncEncodable for IdOrInline<T> {
+    async fn async_encode<W: AsyncWrite + Send + Unpin>(
+        &self,
+        writer: &mut W,
+    ) -> anyhow::Result<()> {
+        self.prefix()?.async_encode(writer).await?;
+        match self {
+            IdOrInline::Id(_) => Ok(()),
+            IdOrInline::Inline(item) => item.async_encode(writer).await,
+        }
+    }
+}
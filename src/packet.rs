@@ -0,0 +1,190 @@
+use crate::encoder::*;
+use crate::limits::DecodeLimits;
+use crate::nums::VarInt;
+use anyhow::Context;
+use bytes::Buf;
+use std::convert::TryFrom;
+use std::io::{Read, Write};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// The largest frame length a vanilla server accepts, the largest value of a 3 byte VarInt.
+pub const MAX_PACKET_LENGTH: i32 = 2097151;
+
+/// A single uncompressed frame: `VarInt` length, `VarInt` packet id and the remaining body.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RawPacket {
+    pub id: VarInt,
+    pub data: Vec<u8>,
+}
+
+impl RawPacket {
+    pub fn new<I: Into<VarInt>>(id: I, data: Vec<u8>) -> Self {
+        RawPacket {
+            id: id.into(),
+            data,
+        }
+    }
+
+    pub fn from_body(body: &[u8]) -> anyhow::Result<Self> {
+        let mut cursor = std::io::Cursor::new(body);
+        let id = VarInt::decode(&mut cursor).context("Failed to read packet id from frame.")?;
+        let data = body[cursor.position() as usize..].to_vec();
+        Ok(RawPacket { id, data })
+    }
+
+    pub fn from_packet<I: Into<VarInt>, T: Encodable>(id: I, packet: &T) -> anyhow::Result<Self> {
+        let mut data = Vec::with_capacity(usize::try_from(packet.size()?)?);
+        packet.encode_buf(&mut data)?;
+        Ok(RawPacket::new(id, data))
+    }
+
+    pub fn reader(&self) -> std::io::Cursor<&[u8]> {
+        std::io::Cursor::new(&self.data)
+    }
+
+    /// Decodes the body as `T`, failing if any bytes are left over.
+    pub fn decode_packet<T: Decodable>(&self) -> anyhow::Result<T> {
+        self.decode_packet_limited(&DecodeLimits::default())
+    }
+
+    /// Decodes the body as `T` under `limits`, with the allocation budget starting afresh.
+    pub fn decode_packet_limited<T: Decodable>(&self, limits: &DecodeLimits) -> anyhow::Result<T> {
+        let mut limits = limits.clone();
+        limits.reset();
+        let mut reader = self.reader();
+        let decoded = T::decode_limited(&mut reader, &mut limits)?;
+        let remaining = self.data.len() as u64 - reader.position();
+        if remaining != 0 {
+            anyhow::bail!(
+                "Packet {} was not fully read, {} bytes remain.",
+                self.id,
+                remaining
+            );
+        }
+        Ok(decoded)
+    }
+
+    pub async fn async_decode<R: AsyncRead + Send + Unpin>(reader: &mut R) -> anyhow::Result<Self> {
+        let mut length = 0i32;
+        for position in 0..3 {
+            let byte = reader
+                .read_u8()
+                .await
+                .context("Unexpected EOF while reading packet length.")?;
+            length |= i32::from(byte & 0x7F) << (7 * position);
+            if byte & 0x80 == 0 {
+                break;
+            }
+            if position == 2 {
+                anyhow::bail!("Failed to decode packet length, too many bytes.");
+            }
+        }
+        if length < 1 {
+            anyhow::bail!("Malformed packet length found. Length {}", length);
+        }
+
+        let mut body = vec![0u8; length as usize];
+        reader.read_exact(&mut body).await.context(format!(
+            "Unexpected EOF while reading packet with length {}.",
+            length
+        ))?;
+        RawPacket::from_body(&body)
+    }
+
+    fn body_length(&self) -> anyhow::Result<VarInt> {
+        let length = self
+            .id
+            .size()?
+            .try_add(VarInt::try_from(self.data.len())?)?;
+        if length > MAX_PACKET_LENGTH {
+            anyhow::bail!(
+                "Failed to frame packet with length {}, limit is {}.",
+                length,
+                MAX_PACKET_LENGTH
+            );
+        }
+        Ok(length)
+    }
+}
+
+impl Decodable for RawPacket {
+    fn decode<R: Read>(reader: &mut R) -> anyhow::Result<Self> {
+        Self::decode_limited(reader, &mut DecodeLimits::default())
+    }
+
+    fn decode_buf<B: bytes::Buf>(buf: &mut B) -> anyhow::Result<Self> {
+        Self::decode_buf_limited(buf, &mut DecodeLimits::default())
+    }
+
+    fn decode_buf_limited<B: bytes::Buf>(
+        buf: &mut B,
+        limits: &mut DecodeLimits,
+    ) -> anyhow::Result<Self> {
+        let length = VarInt::decode_buf(buf)?;
+        if !(1..=MAX_PACKET_LENGTH).contains(&*length) {
+            anyhow::bail!("Malformed packet length found. Length {}", length);
+        }
+        limits.allocate(*length as usize)?;
+
+        let length = *length as usize;
+        check_remaining(buf, length, "packet")?;
+        let mut body = buf.take(length);
+        let id = VarInt::decode_buf(&mut body).context("Failed to read packet id from frame.")?;
+        let mut data = vec![0u8; body.remaining()];
+        body.copy_to_slice(&mut data);
+        Ok(RawPacket { id, data })
+    }
+
+    fn decode_limited<R: Read>(reader: &mut R, limits: &mut DecodeLimits) -> anyhow::Result<Self> {
+        let length = VarInt::decode(reader)?;
+        if !(1..=MAX_PACKET_LENGTH).contains(&*length) {
+            anyhow::bail!("Malformed packet length found. Length {}", length);
+        }
+        limits.allocate(*length as usize)?;
+
+        let mut body = vec![0u8; *length as usize];
+        reader.read_exact(&mut body).context(format!(
+            "Unexpected EOF while reading packet with length {}.",
+            length
+        ))?;
+        RawPacket::from_body(&body)
+    }
+}
+
+impl Encodable for RawPacket {
+    fn encode<W: Write>(&self, writer: &mut W) -> anyhow::Result<()> {
+        self.body_length()?.encode(writer)?;
+        self.id.encode(writer)?;
+        writer
+            .write_all(&self.data)
+            .context("Failed to write packet body into buffer.")
+    }
+
+    fn size(&self) -> anyhow::Result<VarInt> {
+        let length = self.body_length()?;
+        length.size()?.try_add(length)
+    }
+
+    fn encode_buf<B: bytes::BufMut>(&self, buf: &mut B) -> anyhow::Result<()> {
+        self.body_length()?.encode_buf(buf)?;
+        self.id.encode_buf(buf)?;
+        check_remaining_mut(buf, self.data.len(), "packet body")?;
+        buf.put_slice(&self.data);
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl AsyncEncodable for RawPacket {
+    async fn async_encode<W: AsyncWrite + Send + Unpin>(
+        &self,
+        writer: &mut W,
+    ) -> anyhow::Result<()> {
+        self.body_length()?.async_encode(writer).await?;
+        self.id.async_encode(writer).await?;
+        writer
+            .write_all(&self.data)
+            .await
+            .context("Failed to write packet body into buffer.")
+    }
+}
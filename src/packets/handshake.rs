@@ -0,0 +1,152 @@
+use crate::auto_string;
+use crate::encoder::*;
+use crate::nums::VarInt;
+use crate::state::ConnectionState;
+use std::convert::TryFrom;
+use std::io::{Read, Write};
+use tokio::io::AsyncWrite;
+
+auto_string!(ServerAddress, 255);
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum NextState {
+    Status,
+    Login,
+    Transfer,
+}
+
+impl From<NextState> for ConnectionState {
+    fn from(next_state: NextState) -> Self {
+        match next_state {
+            NextState::Status => ConnectionState::Status,
+            NextState::Login | NextState::Transfer => ConnectionState::Login,
+        }
+    }
+}
+
+impl From<NextState> for VarInt {
+    fn from(next_state: NextState) -> Self {
+        match next_state {
+            NextState::Status => VarInt::from(1),
+            NextState::Login => VarInt::from(2),
+            NextState::Transfer => VarInt::from(3),
+        }
+    }
+}
+
+impl std::convert::TryFrom<VarInt> for NextState {
+    type Error = anyhow::Error;
+
+    fn try_from(next_state: VarInt) -> anyhow::Result<Self> {
+        match *next_state {
+            1 => Ok(NextState::Status),
+            2 => Ok(NextState::Login),
+            3 => Ok(NextState::Transfer),
+            _ => anyhow::bail!("Unknown handshake next state {}.", next_state),
+        }
+    }
+}
+
+impl Decodable for NextState {
+    fn decode<R: Read>(reader: &mut R) -> anyhow::Result<Self> {
+        NextState::try_from(VarInt::decode(reader)?)
+    }
+
+    fn decode_buf<B: bytes::Buf>(buf: &mut B) -> anyhow::Result<Self> {
+        NextState::try_from(VarInt::decode_buf(buf)?)
+    }
+}
+
+impl Encodable for NextState {
+    fn encode<W: Write>(&self, writer: &mut W) -> anyhow::Result<()> {
+        VarInt::from(*self).encode(writer)
+    }
+
+    fn size(&self) -> anyhow::Result<VarInt> {
+        VarInt::from(*self).size()
+    }
+
+    fn encode_buf<B: bytes::BufMut>(&self, buf: &mut B) -> anyhow::Result<()> {
+        VarInt::from(*self).encode_buf(buf)
+    }
+}
+
+#[async_trait::async_trait]
+impl AsyncEncodable for NextState {
+    async fn async_encode<W: AsyncWrite + Send + Unpin>(
+        &self,
+        writer: &mut W,
+    ) -> anyhow::Result<()> {
+        VarInt::from(*self).async_encode(writer).await
+    }
+}
+
+/// The first serverbound packet of every connection.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Handshake {
+    pub protocol_version: VarInt,
+    pub server_address: ServerAddress,
+    pub server_port: u16,
+    pub next_state: NextState,
+}
+
+impl Handshake {
+    pub const ID: i32 = 0x00;
+}
+
+impl Decodable for Handshake {
+    fn decode<R: Read>(reader: &mut R) -> anyhow::Result<Self> {
+        Ok(Handshake {
+            protocol_version: VarInt::decode(reader)?,
+            server_address: ServerAddress::decode(reader)?,
+            server_port: u16::decode(reader)?,
+            next_state: NextState::decode(reader)?,
+        })
+    }
+
+    fn decode_buf<B: bytes::Buf>(buf: &mut B) -> anyhow::Result<Self> {
+        Ok(Handshake {
+            protocol_version: VarInt::decode_buf(buf)?,
+            server_address: ServerAddress::decode_buf(buf)?,
+            server_port: u16::decode_buf(buf)?,
+            next_state: NextState::decode_buf(buf)?,
+        })
+    }
+}
+
+impl Encodable for Handshake {
+    fn encode<W: Write>(&self, writer: &mut W) -> anyhow::Result<()> {
+        self.protocol_version.encode(writer)?;
+        self.server_address.encode(writer)?;
+        self.server_port.encode(writer)?;
+        self.next_state.encode(writer)
+    }
+
+    fn size(&self) -> anyhow::Result<VarInt> {
+        self.protocol_version
+            .size()?
+            .try_add(self.server_address.size()?)?
+            .try_add(self.server_port.size()?)?
+            .try_add(self.next_state.size()?)
+    }
+
+    fn encode_buf<B: bytes::BufMut>(&self, buf: &mut B) -> anyhow::Result<()> {
+        self.protocol_version.encode_buf(buf)?;
+        self.server_address.encode_buf(buf)?;
+        self.server_port.encode_buf(buf)?;
+        self.next_state.encode_buf(buf)
+    }
+}
+
+#[async_trait::async_trait]
+impl AsyncEncodable for Handshake {
+    async fn async_encode<W: AsyncWrite + Send + Unpin>(
+        &self,
+        writer: &mut W,
+    ) -> anyhow::Result<()> {
+        self.protocol_version.async_encode(writer).await?;
+        self.server_address.async_encode(writer).await?;
+        self.server_port.async_encode(writer).await?;
+        self.next_state.async_encode(writer).await
+    }
+}
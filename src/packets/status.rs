@@ -0,0 +1,262 @@
+use crate::auto_string;
+use crate::common::Chat;
+use crate::encoder::*;
+use crate::nums::VarInt;
+use crate::strings::McString;
+use anyhow::Context;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use tokio::io::AsyncWrite;
+use uuid::Uuid;
+
+auto_string!(StatusJson, 32767);
+
+const FAVICON_PREFIX: &str = "data:image/png;base64,";
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct StatusVersion {
+    pub name: String,
+    pub protocol: i32,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct PlayerSample {
+    pub name: String,
+    pub id: Uuid,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct StatusPlayers {
+    pub max: i32,
+    pub online: i32,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sample: Vec<PlayerSample>,
+}
+
+/// A `data:image/png;base64,` URI holding the 64x64 server icon.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Favicon(String);
+
+impl Favicon {
+    pub fn from_png(png: &[u8]) -> Self {
+        Favicon(format!(
+            "{}{}",
+            FAVICON_PREFIX,
+            base64::engine::general_purpose::STANDARD.encode(png)
+        ))
+    }
+
+    pub fn uri(&self) -> &str {
+        &self.0
+    }
+
+    pub fn png(&self) -> anyhow::Result<Vec<u8>> {
+        let encoded = match self.0.strip_prefix(FAVICON_PREFIX) {
+            Some(encoded) => encoded,
+            None => anyhow::bail!("Malformed favicon found, expected a PNG data URI."),
+        };
+        // vanilla servers used to wrap the base64 body every 76 characters
+        let encoded: String = encoded.split_whitespace().collect();
+        base64::engine::general_purpose::STANDARD
+            .decode(encoded)
+            .context("Failed to decode favicon.")
+    }
+}
+
+impl From<String> for Favicon {
+    fn from(uri: String) -> Self {
+        Favicon(uri)
+    }
+}
+
+/// The JSON document sent in Status Response.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerStatus {
+    pub version: StatusVersion,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub players: Option<StatusPlayers>,
+    #[serde(default = "empty_description", with = "chat_json")]
+    pub description: Chat,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub favicon: Option<Favicon>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enforces_secure_chat: Option<bool>,
+}
+
+fn empty_description() -> Chat {
+    Chat::from("\"\"")
+}
+
+/// Embeds the JSON text held by a `Chat` as a JSON value rather than a string.
+mod chat_json {
+    use crate::common::Chat;
+    use crate::strings::McString;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(chat: &Chat, serializer: S) -> Result<S::Ok, S::Error> {
+        match serde_json::from_str::<serde_json::Value>(chat.string()) {
+            Ok(value) => value.serialize(serializer),
+            Err(_) => chat.string().serialize(serializer),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Chat, D::Error> {
+        Ok(Chat::from(
+            serde_json::Value::deserialize(deserializer)?.to_string(),
+        ))
+    }
+}
+
+impl ServerStatus {
+    pub fn to_json(&self) -> anyhow::Result<StatusJson> {
+        Ok(StatusJson::from(
+            serde_json::to_string(self).context("Failed to serialize server status.")?,
+        ))
+    }
+
+    pub fn from_json(json: &StatusJson) -> anyhow::Result<Self> {
+        serde_json::from_str(json.string()).context("Failed to parse server status.")
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct StatusRequest;
+
+impl StatusRequest {
+    pub const ID: i32 = 0x00;
+}
+
+impl Decodable for StatusRequest {
+    fn decode<R: Read>(_: &mut R) -> anyhow::Result<Self> {
+        Ok(StatusRequest)
+    }
+
+    fn decode_buf<B: bytes::Buf>(_: &mut B) -> anyhow::Result<Self> {
+        Ok(StatusRequest)
+    }
+}
+
+impl Encodable for StatusRequest {
+    fn encode<W: Write>(&self, _: &mut W) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn size(&self) -> anyhow::Result<VarInt> {
+        Ok(VarInt::from(0))
+    }
+
+    fn encode_buf<B: bytes::BufMut>(&self, _: &mut B) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl AsyncEncodable for StatusRequest {
+    async fn async_encode<W: AsyncWrite + Send + Unpin>(&self, _: &mut W) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StatusResponse {
+    pub status: ServerStatus,
+}
+
+impl StatusResponse {
+    pub const ID: i32 = 0x00;
+}
+
+impl Decodable for StatusResponse {
+    fn decode<R: Read>(reader: &mut R) -> anyhow::Result<Self> {
+        Ok(StatusResponse {
+            status: ServerStatus::from_json(&StatusJson::decode(reader)?)?,
+        })
+    }
+
+    fn decode_buf<B: bytes::Buf>(buf: &mut B) -> anyhow::Result<Self> {
+        Ok(StatusResponse {
+            status: ServerStatus::from_json(&StatusJson::decode_buf(buf)?)?,
+        })
+    }
+}
+
+impl Encodable for StatusResponse {
+    fn encode<W: Write>(&self, writer: &mut W) -> anyhow::Result<()> {
+        self.status.to_json()?.encode(writer)
+    }
+
+    fn size(&self) -> anyhow::Result<VarInt> {
+        self.status.to_json()?.size()
+    }
+
+    fn encode_buf<B: bytes::BufMut>(&self, buf: &mut B) -> anyhow::Result<()> {
+        self.status.to_json()?.encode_buf(buf)
+    }
+}
+
+#[async_trait::async_trait]
+impl AsyncEncodable for StatusResponse {
+    async fn async_encode<W: AsyncWrite + Send + Unpin>(
+        &self,
+        writer: &mut W,
+    ) -> anyhow::Result<()> {
+        self.status.to_json()?.async_encode(writer).await
+    }
+}
+
+macro_rules! declare_ping_packet {
+    ($name:ident, $id:literal) => {
+        #[derive(Copy, Clone, Debug, Eq, PartialEq)]
+        pub struct $name {
+            pub payload: i64,
+        }
+
+        impl $name {
+            pub const ID: i32 = $id;
+        }
+
+        impl Decodable for $name {
+            fn decode<R: Read>(reader: &mut R) -> anyhow::Result<Self> {
+                Ok($name {
+                    payload: i64::decode(reader)?,
+                })
+            }
+
+            fn decode_buf<B: bytes::Buf>(buf: &mut B) -> anyhow::Result<Self> {
+                Ok($name {
+                    payload: i64::decode_buf(buf)?,
+                })
+            }
+        }
+
+        impl Encodable for $name {
+            fn encode<W: Write>(&self, writer: &mut W) -> anyhow::Result<()> {
+                self.payload.encode(writer)
+            }
+
+            fn size(&self) -> anyhow::Result<VarInt> {
+                self.payload.size()
+            }
+
+            fn encode_buf<B: bytes::BufMut>(&self, buf: &mut B) -> anyhow::Result<()> {
+                self.payload.encode_buf(buf)
+            }
+        }
+
+        #[async_trait::async_trait]
+        impl AsyncEncodable for $name {
+            async fn async_encode<W: AsyncWrite + Send + Unpin>(
+                &self,
+                writer: &mut W,
+            ) -> anyhow::Result<()> {
+                self.payload.async_encode(writer).await
+            }
+        }
+    };
+}
+
+declare_ping_packet!(PingRequest, 0x01);
+declare_ping_packet!(PongResponse, 0x01);
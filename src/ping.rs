@@ -0,0 +1,263 @@
+use crate::encoder::*;
+use crate::legacy::*;
+use crate::nums::VarInt;
+use crate::packet::RawPacket;
+use crate::packets::handshake::{Handshake, NextState};
+use crate::packets::status::*;
+use anyhow::Context;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+/// By convention a client pinging to find out which version to use sends protocol -1.
+pub const UNKNOWN_PROTOCOL_VERSION: i32 = -1;
+
+#[derive(Clone, Debug)]
+pub struct PingOptions {
+    pub connect_timeout: Duration,
+    pub read_timeout: Duration,
+    pub protocol_version: i32,
+    /// The host name sent in the handshake, defaults to the IP address being pinged.
+    pub server_address: Option<String>,
+}
+
+impl Default for PingOptions {
+    fn default() -> Self {
+        PingOptions {
+            connect_timeout: Duration::from_secs(5),
+            read_timeout: Duration::from_secs(5),
+            protocol_version: UNKNOWN_PROTOCOL_VERSION,
+            server_address: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct PingResult {
+    pub status: ServerStatus,
+    pub latency: Duration,
+}
+
+async fn with_timeout<T>(
+    timeout: Duration,
+    action: &str,
+    future: impl std::future::Future<Output = anyhow::Result<T>>,
+) -> anyhow::Result<T> {
+    match tokio::time::timeout(timeout, future).await {
+        Ok(result) => result,
+        Err(_) => anyhow::bail!("Timed out after {:?} while {}.", timeout, action),
+    }
+}
+
+async fn write_packet<W: AsyncWrite + Send + Unpin, T: Encodable>(
+    writer: &mut W,
+    id: i32,
+    packet: &T,
+) -> anyhow::Result<()> {
+    RawPacket::from_packet(id, packet)?
+        .async_encode(writer)
+        .await?;
+    writer.flush().await.context("Failed to flush packet.")
+}
+
+async fn read_packet<R: AsyncRead + Send + Unpin, T: Decodable>(
+    reader: &mut R,
+    id: i32,
+    timeout: Duration,
+) -> anyhow::Result<T> {
+    let packet = with_timeout(timeout, "reading a packet", RawPacket::async_decode(reader)).await?;
+    if packet.id != id {
+        anyhow::bail!("Expected packet id {} but found {}.", id, packet.id);
+    }
+    packet.decode_packet()
+}
+
+/// Pings the server at `addr` with the default options. No SRV lookup is performed, callers
+/// resolve host names themselves.
+pub async fn ping(addr: SocketAddr) -> anyhow::Result<PingResult> {
+    ping_with(addr, &PingOptions::default()).await
+}
+
+pub async fn ping_with(addr: SocketAddr, options: &PingOptions) -> anyhow::Result<PingResult> {
+    let mut stream = with_timeout(options.connect_timeout, "connecting", async {
+        TcpStream::connect(addr)
+            .await
+            .context(format!("Failed to connect to {}.", addr))
+    })
+    .await?;
+    stream.set_nodelay(true)?;
+
+    let host = match &options.server_address {
+        Some(host) => host.clone(),
+        None => addr.ip().to_string(),
+    };
+    ping_stream(&mut stream, &host, addr.port(), options).await
+}
+
+/// Runs the status exchange over an already established stream.
+pub async fn ping_stream<S: AsyncRead + AsyncWrite + Send + Unpin>(
+    stream: &mut S,
+    host: &str,
+    port: u16,
+    options: &PingOptions,
+) -> anyhow::Result<PingResult> {
+    let handshake = Handshake {
+        protocol_version: VarInt::from(options.protocol_version),
+        server_address: host.into(),
+        server_port: port,
+        next_state: NextState::Status,
+    };
+    write_packet(stream, Handshake::ID, &handshake).await?;
+    write_packet(stream, StatusRequest::ID, &StatusRequest).await?;
+    let response: StatusResponse =
+        read_packet(stream, StatusResponse::ID, options.read_timeout).await?;
+
+    let started = Instant::now();
+    let payload = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as i64)
+        .unwrap_or_default();
+    write_packet(stream, PingRequest::ID, &PingRequest { payload }).await?;
+    let pong: PongResponse = read_packet(stream, PongResponse::ID, options.read_timeout).await?;
+    let latency = started.elapsed();
+    if pong.payload != payload {
+        anyhow::bail!(
+            "Expected pong payload {} but found {}.",
+            payload,
+            pong.payload
+        );
+    }
+
+    Ok(PingResult {
+        status: response.status,
+        latency,
+    })
+}
+
+/// Answers server list pings with a fixed `ServerStatus`.
+#[derive(Clone, Debug)]
+pub struct StatusResponder {
+    pub status: ServerStatus,
+    pub read_timeout: Duration,
+    /// How long to wait after `0xFE` for the rest of a 1.4+ legacy ping before answering in
+    /// the beta format.
+    pub legacy_grace: Duration,
+}
+
+impl StatusResponder {
+    pub fn new(status: ServerStatus) -> Self {
+        StatusResponder {
+            status,
+            read_timeout: Duration::from_secs(5),
+            legacy_grace: Duration::from_millis(100),
+        }
+    }
+
+    /// Reads the handshake and answers the status exchange, including pre-1.7 legacy pings. A
+    /// handshake for any other state is returned untouched so the caller can carry on with login
+    /// on the same stream.
+    pub async fn serve<S: AsyncRead + AsyncWrite + Send + Unpin>(
+        &self,
+        stream: &mut S,
+    ) -> anyhow::Result<Option<Handshake>> {
+        let first = with_timeout(self.read_timeout, "reading the handshake", async {
+            stream
+                .read_u8()
+                .await
+                .context("Unexpected EOF while reading the handshake.")
+        })
+        .await?;
+        if is_legacy_ping(first) {
+            self.respond_legacy(stream).await?;
+            return Ok(None);
+        }
+
+        let first = [first];
+        let mut reader = (&first[..]).chain(&mut *stream);
+        let handshake: Handshake =
+            read_packet(&mut reader, Handshake::ID, self.read_timeout).await?;
+        if handshake.next_state != NextState::Status {
+            return Ok(Some(handshake));
+        }
+        self.respond(stream).await?;
+        Ok(None)
+    }
+
+    /// Answers a legacy ping on a stream which has already sent the `0xFE` prefix.
+    pub async fn respond_legacy<S: AsyncRead + AsyncWrite + Send + Unpin>(
+        &self,
+        stream: &mut S,
+    ) -> anyhow::Result<()> {
+        let request = match tokio::time::timeout(self.legacy_grace, stream.read_u8()).await {
+            Ok(Ok(0x01)) => {
+                match tokio::time::timeout(self.legacy_grace, read_ping_host(stream)).await {
+                    Ok(Ok(request)) => request,
+                    _ => LegacyPingRequest::V1_4,
+                }
+            }
+            _ => LegacyPingRequest::Beta,
+        };
+
+        let mut bytes = Vec::new();
+        LegacyPingResponse::from_status(&self.status).encode_for(&request, &mut bytes)?;
+        stream
+            .write_all(&bytes)
+            .await
+            .context("Failed to write legacy ping response.")?;
+        stream.flush().await.context("Failed to flush packet.")
+    }
+
+    /// Answers the status exchange on a stream which has already sent a status handshake.
+    pub async fn respond<S: AsyncRead + AsyncWrite + Send + Unpin>(
+        &self,
+        stream: &mut S,
+    ) -> anyhow::Result<()> {
+        read_packet::<_, StatusRequest>(stream, StatusRequest::ID, self.read_timeout).await?;
+        let response = StatusResponse {
+            status: self.status.clone(),
+        };
+        write_packet(stream, StatusResponse::ID, &response).await?;
+
+        // clients may hang up without measuring latency, but only before sending a byte of it
+        let first = with_timeout(self.read_timeout, "reading a packet", async {
+            match stream.read_u8().await {
+                Ok(first) => Ok(Some(first)),
+                Err(error) if error.kind() == std::io::ErrorKind::UnexpectedEof => Ok(None),
+                Err(error) => Err(error).context("Failed to read the ping request."),
+            }
+        })
+        .await?;
+        let first = match first {
+            Some(first) => [first],
+            None => return Ok(()),
+        };
+        let mut reader = (&first[..]).chain(&mut *stream);
+        let ping: PingRequest =
+            read_packet(&mut reader, PingRequest::ID, self.read_timeout).await?;
+        write_packet(
+            stream,
+            PongResponse::ID,
+            &PongResponse {
+                payload: ping.payload,
+            },
+        )
+        .await
+    }
+}
+
+async fn read_ping_host<R: AsyncRead + Send + Unpin>(
+    reader: &mut R,
+) -> anyhow::Result<LegacyPingRequest> {
+    // packet id, then the channel name "MC|PingHost" as 11 UTF-16 code units
+    let mut header = [0u8; 25];
+    reader.read_exact(&mut header).await?;
+    let length = reader.read_u16().await?;
+    let mut rest = vec![0u8; usize::from(length)];
+    reader.read_exact(&mut rest).await?;
+
+    let mut bytes = header.to_vec();
+    bytes.extend_from_slice(&length.to_be_bytes());
+    bytes.extend_from_slice(&rest);
+    LegacyPingRequest::decode_ping_host(&mut std::io::Cursor::new(bytes))
+}
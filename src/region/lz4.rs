@@ -0,0 +1,113 @@
+//! The block stream of lz4-java's `LZ4BlockOutputStream`, which region files use for LZ4 chunks.
+//!
+//! Each block is `LZ4Block`, a token holding the method and level, the compressed and original
+//! lengths and a 28 bit XXH32 checksum of the original bytes, all little-endian. An empty block
+//! ends the stream. The blocks themselves are plain LZ4, left to `lz4_flex`.
+
+use anyhow::Context;
+use std::convert::TryFrom;
+use twox_hash::XxHash32;
+
+const MAGIC: &[u8; 8] = b"LZ4Block";
+const HEADER_LENGTH: usize = 21;
+const METHOD_RAW: u8 = 0x10;
+const METHOD_LZ4: u8 = 0x20;
+/// lz4-java's default 64KiB blocks, stored as a power of two above 1KiB.
+const BLOCK_SIZE: usize = 1 << 16;
+const COMPRESSION_LEVEL: u8 = 6;
+const CHECKSUM_SEED: u32 = 0x9747_b28c;
+
+fn read_u32_le(bytes: &[u8]) -> u32 {
+    u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+fn checksum(bytes: &[u8]) -> u32 {
+    XxHash32::oneshot(CHECKSUM_SEED, bytes) & 0x0fff_ffff
+}
+
+fn write_block(output: &mut Vec<u8>, method: u8, block: &[u8], original: &[u8]) {
+    output.extend_from_slice(MAGIC);
+    output.push(method | COMPRESSION_LEVEL);
+    output.extend_from_slice(&(block.len() as u32).to_le_bytes());
+    output.extend_from_slice(&(original.len() as u32).to_le_bytes());
+    // the end block has a zero checksum rather than that of no bytes
+    let checksum = if original.is_empty() { 0 } else { checksum(original) };
+    output.extend_from_slice(&checksum.to_le_bytes());
+    output.extend_from_slice(block);
+}
+
+/// Compresses into a block stream `LZ4BlockInputStream` can read, storing blocks that don't
+/// shrink as raw bytes.
+pub(crate) fn compress(input: &[u8]) -> Vec<u8> {
+    let mut output = Vec::with_capacity(input.len() / 2 + HEADER_LENGTH * 2);
+    for original in input.chunks(BLOCK_SIZE) {
+        let block = lz4_flex::block::compress(original);
+        if block.len() < original.len() {
+            write_block(&mut output, METHOD_LZ4, &block, original);
+        } else {
+            write_block(&mut output, METHOD_RAW, original, original);
+        }
+    }
+    write_block(&mut output, METHOD_RAW, &[], &[]);
+    output
+}
+
+/// Decompresses a block stream up to its empty end block, failing once the output would pass
+/// `limit` bytes.
+pub(crate) fn decompress(mut input: &[u8], limit: usize) -> anyhow::Result<Vec<u8>> {
+    let mut output = Vec::new();
+    loop {
+        // like lz4-java, a stream which stops before its end block is truncated
+        let header = input
+            .get(..HEADER_LENGTH)
+            .context("Unexpected EOF while reading LZ4 block header.")?;
+        if &header[..8] != MAGIC {
+            anyhow::bail!("Malformed LZ4 block found, bad magic.");
+        }
+        let method = header[8] & 0xf0;
+        let compressed = usize::try_from(read_u32_le(&header[9..]))?;
+        let original = usize::try_from(read_u32_le(&header[13..]))?;
+        let expected = read_u32_le(&header[17..]);
+        input = &input[HEADER_LENGTH..];
+
+        if original == 0 && compressed == 0 {
+            if expected != 0 {
+                anyhow::bail!("Malformed LZ4 end block found, checksum {}.", expected);
+            }
+            return Ok(output);
+        }
+        if original > 1 << (10 + (header[8] & 0x0f)) || original > limit - output.len() {
+            anyhow::bail!("Failed to decompress LZ4 block of {} bytes.", original);
+        }
+        let block = input
+            .get(..compressed)
+            .context("Unexpected EOF while reading LZ4 block.")?;
+        input = &input[compressed..];
+
+        let start = output.len();
+        match method {
+            METHOD_RAW if compressed == original => output.extend_from_slice(block),
+            METHOD_LZ4 => {
+                output.resize(start + original, 0);
+                let written = lz4_flex::block::decompress_into(block, &mut output[start..])
+                    .context("Failed to decompress LZ4 block.")?;
+                if written != original {
+                    anyhow::bail!(
+                        "Failed to decompress LZ4 block, expected {} bytes but found {}.",
+                        original,
+                        written
+                    );
+                }
+            }
+            _ => anyhow::bail!(
+                "Malformed LZ4 block found. Method {}, {} bytes for {}",
+                method,
+                compressed,
+                original
+            ),
+        }
+        if checksum(&output[start..]) != expected {
+            anyhow::bail!("Failed to decompress LZ4 block, checksum mismatch.");
+        }
+    }
+}
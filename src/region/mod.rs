@@ -0,0 +1,370 @@
+use crate::encoder::*;
+use crate::limits::DecodeLimits;
+use anyhow::Context;
+use nbt::Blob;
+use std::convert::TryFrom;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+mod lz4;
+
+pub const SECTOR_BYTES: usize = 4096;
+/// The location and timestamp tables, a sector each.
+const HEADER_SECTORS: usize = 2;
+const CHUNKS: usize = 1024;
+/// The largest chunk stored in the region itself. Bigger ones go to an `.mcc` file.
+const MAX_SECTORS: usize = 255;
+/// Set on the compression type of a chunk stored in an `.mcc` file.
+const EXTERNAL: u8 = 0x80;
+
+/// How a chunk's NBT is compressed on disk.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Compression {
+    Gzip,
+    Zlib,
+    None,
+    /// lz4-java's block stream, written since 1.20.5.
+    Lz4,
+}
+
+impl Compression {
+    fn id(self) -> u8 {
+        match self {
+            Compression::Gzip => 1,
+            Compression::Zlib => 2,
+            Compression::None => 3,
+            Compression::Lz4 => 4,
+        }
+    }
+
+    fn from_id(id: u8) -> anyhow::Result<Self> {
+        match id {
+            1 => Ok(Compression::Gzip),
+            2 => Ok(Compression::Zlib),
+            3 => Ok(Compression::None),
+            4 => Ok(Compression::Lz4),
+            127 => anyhow::bail!("Failed to read chunk with custom compression."),
+            id => anyhow::bail!("Malformed chunk compression found. Id {}", id),
+        }
+    }
+
+    fn compress(self, bytes: &[u8]) -> anyhow::Result<Vec<u8>> {
+        match self {
+            Compression::Gzip => {
+                let mut encoder =
+                    flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(bytes)?;
+                Ok(encoder.finish()?)
+            }
+            Compression::Zlib => {
+                let mut encoder =
+                    flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(bytes)?;
+                Ok(encoder.finish()?)
+            }
+            Compression::None => Ok(bytes.to_vec()),
+            Compression::Lz4 => Ok(lz4::compress(bytes)),
+        }
+    }
+
+    fn decode(self, bytes: &[u8], limits: &mut DecodeLimits) -> anyhow::Result<Blob> {
+        match self {
+            Compression::Gzip => {
+                Blob::decode_limited(&mut flate2::read::GzDecoder::new(bytes), limits)
+            }
+            Compression::Zlib => {
+                Blob::decode_limited(&mut flate2::read::ZlibDecoder::new(bytes), limits)
+            }
+            Compression::None => Blob::decode_limited(&mut &bytes[..], limits),
+            Compression::Lz4 => {
+                let bytes = lz4::decompress(bytes, limits.max_nbt_bytes)?;
+                Blob::decode_limited(&mut &bytes[..], limits)
+            }
+        }
+    }
+}
+
+/// An Anvil region file, `r.<x>.<z>.mca`, holding 32x32 chunks.
+///
+/// Chunks are addressed by their chunk coordinates in the world. Each is stored in whole
+/// sectors after the header tables, found through its 3 byte sector offset and 1 byte sector
+/// count. Writes reuse the first free run of sectors that fits.
+pub struct Region {
+    file: File,
+    directory: PathBuf,
+    x: i32,
+    z: i32,
+    locations: [u32; CHUNKS],
+    timestamps: [i32; CHUNKS],
+    used: Vec<bool>,
+    limits: DecodeLimits,
+}
+
+/// Reads the region coordinates out of an `r.<x>.<z>.mca` file name.
+fn region_coordinates(path: &Path) -> Option<(i32, i32)> {
+    let name = path.file_name()?.to_str()?;
+    let mut parts = name.strip_prefix("r.")?.strip_suffix(".mca")?.split('.');
+    let x = parts.next()?.parse().ok()?;
+    let z = parts.next()?.parse().ok()?;
+    match parts.next() {
+        Some(_) => None,
+        None => Some((x, z)),
+    }
+}
+
+impl Region {
+    /// Opens a region file, creating it with empty tables if it doesn't exist.
+    pub fn open<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let (x, z) = region_coordinates(path).with_context(|| {
+            format!("Failed to read region coordinates from {}.", path.display())
+        })?;
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)
+            .with_context(|| format!("Failed to open region {}.", path.display()))?;
+
+        let mut header = vec![0u8; HEADER_SECTORS * SECTOR_BYTES];
+        let length = file.metadata()?.len();
+        if length == 0 {
+            file.write_all(&header)?;
+        } else {
+            file.read_exact(&mut header)
+                .context("Unexpected EOF while reading region header.")?;
+        }
+
+        let mut locations = [0u32; CHUNKS];
+        let mut timestamps = [0i32; CHUNKS];
+        for (index, entry) in header.chunks_exact(4).enumerate() {
+            let entry = [entry[0], entry[1], entry[2], entry[3]];
+            if index < CHUNKS {
+                locations[index] = u32::from_be_bytes(entry);
+            } else {
+                timestamps[index - CHUNKS] = i32::from_be_bytes(entry);
+            }
+        }
+
+        let sectors = (file.metadata()?.len() as usize).div_ceil(SECTOR_BYTES);
+        let mut used = vec![false; sectors.max(HEADER_SECTORS)];
+        used[..HEADER_SECTORS].fill(true);
+        for location in locations.iter().filter(|location| **location != 0) {
+            let (offset, count) = Self::split_location(*location);
+            if offset >= HEADER_SECTORS {
+                for sector in used.iter_mut().skip(offset).take(count) {
+                    *sector = true;
+                }
+            }
+        }
+
+        Ok(Region {
+            file,
+            directory: path.parent().map(Path::to_path_buf).unwrap_or_default(),
+            x,
+            z,
+            locations,
+            timestamps,
+            used,
+            limits: DecodeLimits::default(),
+        })
+    }
+
+    /// Applies `limits` to the NBT of chunks read afterwards, and to the size of external chunks.
+    pub fn with_limits(mut self, limits: DecodeLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub fn x(&self) -> i32 {
+        self.x
+    }
+
+    pub fn z(&self) -> i32 {
+        self.z
+    }
+
+    fn split_location(location: u32) -> (usize, usize) {
+        ((location >> 8) as usize, (location & 0xff) as usize)
+    }
+
+    fn index(&self, x: i32, z: i32) -> anyhow::Result<usize> {
+        if x >> 5 != self.x || z >> 5 != self.z {
+            anyhow::bail!(
+                "Failed to find chunk {}, {} in region {}, {}.",
+                x,
+                z,
+                self.x,
+                self.z
+            );
+        }
+        Ok(((x & 31) + (z & 31) * 32) as usize)
+    }
+
+    fn external_path(&self, x: i32, z: i32) -> PathBuf {
+        self.directory.join(format!("c.{}.{}.mcc", x, z))
+    }
+
+    pub fn contains(&self, x: i32, z: i32) -> anyhow::Result<bool> {
+        Ok(self.locations[self.index(x, z)?] != 0)
+    }
+
+    /// When the chunk was last saved, in seconds since the Unix epoch.
+    pub fn timestamp(&self, x: i32, z: i32) -> anyhow::Result<i32> {
+        Ok(self.timestamps[self.index(x, z)?])
+    }
+
+    /// Reads a chunk's NBT, or `None` if it was never saved.
+    pub fn read_chunk(&mut self, x: i32, z: i32) -> anyhow::Result<Option<Blob>> {
+        let index = self.index(x, z)?;
+        let (offset, count) = Self::split_location(self.locations[index]);
+        if offset == 0 && count == 0 {
+            return Ok(None);
+        }
+        if offset < HEADER_SECTORS {
+            anyhow::bail!(
+                "Malformed region found. Chunk {}, {} is in the header",
+                x,
+                z
+            );
+        }
+
+        self.file
+            .seek(SeekFrom::Start((offset * SECTOR_BYTES) as u64))?;
+        let length = u32::decode(&mut self.file)? as usize;
+        if length == 0 || length + 4 > count * SECTOR_BYTES {
+            anyhow::bail!(
+                "Malformed region found. Chunk {}, {} is {} bytes in {} sectors",
+                x,
+                z,
+                length,
+                count
+            );
+        }
+        let compression = u8::decode(&mut self.file)?;
+        let mut limits = self.limits.clone();
+        limits.reset();
+        let bytes = if compression & EXTERNAL != 0 {
+            let path = self.external_path(x, z);
+            let file = File::open(&path)
+                .with_context(|| format!("Failed to open external chunk {}.", path.display()))?;
+            // charged before reading, as nothing else bounds an external chunk's size
+            let length = file.metadata()?.len();
+            limits.allocate(usize::try_from(length)?)?;
+            let mut bytes = Vec::with_capacity(length as usize);
+            file.take(length)
+                .read_to_end(&mut bytes)
+                .with_context(|| format!("Failed to read external chunk {}.", path.display()))?;
+            bytes
+        } else {
+            let mut bytes = vec![0u8; length - 1];
+            self.file
+                .read_exact(&mut bytes)
+                .context("Unexpected EOF while reading chunk from region.")?;
+            bytes
+        };
+
+        Compression::from_id(compression & !EXTERNAL)?
+            .decode(&bytes, &mut limits)
+            .map(Some)
+    }
+
+    /// Compresses and stores a chunk's NBT, stamped with the current time.
+    pub fn write_chunk(
+        &mut self,
+        x: i32,
+        z: i32,
+        chunk: &Blob,
+        compression: Compression,
+    ) -> anyhow::Result<()> {
+        let index = self.index(x, z)?;
+        let mut nbt = Vec::new();
+        chunk.encode(&mut nbt)?;
+        let compressed = compression.compress(&nbt)?;
+
+        let external = 5 + compressed.len() > MAX_SECTORS * SECTOR_BYTES;
+        let mut payload = Vec::with_capacity(5 + compressed.len());
+        if external {
+            std::fs::write(self.external_path(x, z), &compressed)?;
+            1u32.encode(&mut payload)?;
+            (compression.id() | EXTERNAL).encode(&mut payload)?;
+        } else {
+            u32::try_from(compressed.len() + 1)?.encode(&mut payload)?;
+            compression.id().encode(&mut payload)?;
+            payload.extend_from_slice(&compressed);
+        }
+        let count = payload.len().div_ceil(SECTOR_BYTES);
+        payload.resize(count * SECTOR_BYTES, 0);
+
+        self.free(index);
+        let offset = self.allocate(count);
+        self.file
+            .seek(SeekFrom::Start((offset * SECTOR_BYTES) as u64))?;
+        self.file.write_all(&payload)?;
+        if !external {
+            Self::remove_external(&self.external_path(x, z))?;
+        }
+
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |time| time.as_secs() as i32);
+        self.write_header(index, ((offset as u32) << 8) | count as u32, timestamp)
+    }
+
+    /// Deletes a chunk, freeing its sectors for later writes.
+    pub fn remove_chunk(&mut self, x: i32, z: i32) -> anyhow::Result<()> {
+        let index = self.index(x, z)?;
+        self.free(index);
+        Self::remove_external(&self.external_path(x, z))?;
+        self.write_header(index, 0, 0)
+    }
+
+    fn remove_external(path: &Path) -> anyhow::Result<()> {
+        match std::fs::remove_file(path) {
+            Err(error) if error.kind() != std::io::ErrorKind::NotFound => Err(error.into()),
+            _ => Ok(()),
+        }
+    }
+
+    fn free(&mut self, index: usize) {
+        let (offset, count) = Self::split_location(self.locations[index]);
+        if offset >= HEADER_SECTORS {
+            for sector in self.used.iter_mut().skip(offset).take(count) {
+                *sector = false;
+            }
+        }
+    }
+
+    /// Claims the first run of `count` free sectors, growing the file if none fits.
+    fn allocate(&mut self, count: usize) -> usize {
+        let mut run = 0;
+        let mut found = None;
+        for (sector, used) in self.used.iter().enumerate() {
+            run = if *used { 0 } else { run + 1 };
+            if run == count {
+                found = Some(sector + 1 - count);
+                break;
+            }
+        }
+        // a free run at the end of the file is extended rather than skipped
+        let offset = found.unwrap_or(self.used.len() - run);
+        if offset + count > self.used.len() {
+            self.used.resize(offset + count, false);
+        }
+        self.used[offset..offset + count].fill(true);
+        offset
+    }
+
+    fn write_header(&mut self, index: usize, location: u32, timestamp: i32) -> anyhow::Result<()> {
+        self.locations[index] = location;
+        self.timestamps[index] = timestamp;
+        self.file.seek(SeekFrom::Start((index * 4) as u64))?;
+        self.file.write_all(&location.to_be_bytes())?;
+        self.file
+            .seek(SeekFrom::Start((SECTOR_BYTES + index * 4) as u64))?;
+        self.file.write_all(&timestamp.to_be_bytes())?;
+        Ok(())
+    }
+}
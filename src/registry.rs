@@ -0,0 +1,215 @@
+use crate::encoder::*;
+use crate::limits::DecodeLimits;
+use crate::packet::RawPacket;
+use crate::state::{ConnectionState, Direction};
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
+
+/// Registers a packet id for every protocol version.
+pub const ALL_PROTOCOLS: RangeInclusive<i32> = 0..=i32::MAX;
+
+/// Declares an enum with one variant per packet type along with the `From` impls required by
+/// `PacketRegistry::register`.
+#[macro_export]
+macro_rules! packet_enum {
+    ($(#[$meta:meta])* $vis:vis enum $name:ident { $($variant:ident($packet:ty)),* $(,)? }) => {
+        $(#[$meta])*
+        $vis enum $name {
+            $($variant($packet),)*
+        }
+
+        $(
+            impl From<$packet> for $name {
+                fn from(packet: $packet) -> Self {
+                    $name::$variant(packet)
+                }
+            }
+        )*
+    };
+}
+
+#[derive(Debug)]
+pub enum DecodedPacket<P> {
+    Known(P),
+    Unknown(RawPacket),
+}
+
+type Decoder<P> = Box<dyn Fn(&RawPacket, &DecodeLimits) -> anyhow::Result<P> + Send + Sync>;
+
+struct Registration<P> {
+    protocols: RangeInclusive<i32>,
+    decoder: Decoder<P>,
+}
+
+type Key = (ConnectionState, Direction);
+type ProtocolIds = Vec<(RangeInclusive<i32>, i32)>;
+
+/// Maps `(state, direction, id)` to typed packets for any number of protocol versions.
+///
+/// Packets decode into `P`, which is either a `Box<dyn Any + Send>` or an enum declared with
+/// `packet_enum!`. Ids which are not registered decode into `DecodedPacket::Unknown` holding the
+/// untouched frame so it can be forwarded as is. Later registrations take precedence over
+/// earlier ones with overlapping protocol ranges.
+pub struct PacketRegistry<P = Box<dyn Any + Send>> {
+    decoders: HashMap<(Key, i32), Vec<Registration<P>>>,
+    ids: HashMap<(Key, TypeId), ProtocolIds>,
+}
+
+impl<P> Default for PacketRegistry<P> {
+    fn default() -> Self {
+        PacketRegistry::new()
+    }
+}
+
+impl<P> PacketRegistry<P> {
+    pub fn new() -> Self {
+        PacketRegistry {
+            decoders: HashMap::new(),
+            ids: HashMap::new(),
+        }
+    }
+
+    pub fn register<T>(
+        &mut self,
+        state: ConnectionState,
+        direction: Direction,
+        ids: impl IntoIterator<Item = (RangeInclusive<i32>, i32)>,
+    ) where
+        T: Decodable + 'static,
+        P: From<T> + 'static,
+    {
+        self.register_with(state, direction, ids, P::from)
+    }
+
+    pub fn register_with<T, F>(
+        &mut self,
+        state: ConnectionState,
+        direction: Direction,
+        ids: impl IntoIterator<Item = (RangeInclusive<i32>, i32)>,
+        map: F,
+    ) where
+        T: Decodable + 'static,
+        F: Fn(T) -> P + Clone + Send + Sync + 'static,
+    {
+        for (protocols, id) in ids {
+            let map = map.clone();
+            self.decoders
+                .entry(((state, direction), id))
+                .or_default()
+                .push(Registration {
+                    protocols: protocols.clone(),
+                    decoder: Box::new(move |packet, limits| {
+                        packet.decode_packet_limited::<T>(limits).map(&map)
+                    }),
+                });
+            self.ids
+                .entry(((state, direction), TypeId::of::<T>()))
+                .or_default()
+                .push((protocols, id));
+        }
+    }
+
+    pub fn id_of<T: 'static>(
+        &self,
+        protocol_version: i32,
+        state: ConnectionState,
+        direction: Direction,
+    ) -> Option<i32> {
+        self.ids
+            .get(&((state, direction), TypeId::of::<T>()))?
+            .iter()
+            .rev()
+            .find(|(protocols, _)| protocols.contains(&protocol_version))
+            .map(|(_, id)| *id)
+    }
+
+    pub fn is_registered(
+        &self,
+        protocol_version: i32,
+        state: ConnectionState,
+        direction: Direction,
+        id: i32,
+    ) -> bool {
+        self.find(protocol_version, state, direction, id).is_some()
+    }
+
+    fn find(
+        &self,
+        protocol_version: i32,
+        state: ConnectionState,
+        direction: Direction,
+        id: i32,
+    ) -> Option<&Registration<P>> {
+        self.decoders
+            .get(&((state, direction), id))?
+            .iter()
+            .rev()
+            .find(|registration| registration.protocols.contains(&protocol_version))
+    }
+
+    pub fn decode(
+        &self,
+        protocol_version: i32,
+        state: ConnectionState,
+        direction: Direction,
+        packet: RawPacket,
+    ) -> anyhow::Result<DecodedPacket<P>> {
+        self.decode_limited(
+            protocol_version,
+            state,
+            direction,
+            packet,
+            &DecodeLimits::default(),
+        )
+    }
+
+    pub fn decode_limited(
+        &self,
+        protocol_version: i32,
+        state: ConnectionState,
+        direction: Direction,
+        packet: RawPacket,
+        limits: &DecodeLimits,
+    ) -> anyhow::Result<DecodedPacket<P>> {
+        match self.find(protocol_version, state, direction, *packet.id) {
+            Some(registration) => Ok(DecodedPacket::Known((registration.decoder)(
+                &packet, limits,
+            )?)),
+            None => Ok(DecodedPacket::Unknown(packet)),
+        }
+    }
+
+    pub fn encode<T: Encodable + 'static>(
+        &self,
+        protocol_version: i32,
+        state: ConnectionState,
+        direction: Direction,
+        packet: &T,
+    ) -> anyhow::Result<RawPacket> {
+        let id = match self.id_of::<T>(protocol_version, state, direction) {
+            Some(id) => id,
+            None => anyhow::bail!(
+                "Failed to find id for {} in {:?} {:?} for protocol {}.",
+                std::any::type_name::<T>(),
+                direction,
+                state,
+                protocol_version
+            ),
+        };
+        RawPacket::from_packet(id, packet)
+    }
+}
+
+impl PacketRegistry<Box<dyn Any + Send>> {
+    pub fn register_any<T: Decodable + Send + 'static>(
+        &mut self,
+        state: ConnectionState,
+        direction: Direction,
+        ids: impl IntoIterator<Item = (RangeInclusive<i32>, i32)>,
+    ) {
+        self.register_with(state, direction, ids, |packet: T| {
+            Box::new(packet) as Box<dyn Any + Send>
+        })
+    }
+}
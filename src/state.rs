@@ -0,0 +1,270 @@
+use crate::encoder::Decodable;
+use crate::packet::RawPacket;
+use crate::packets::handshake::Handshake;
+use anyhow::Context;
+use std::io::Read;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum ConnectionState {
+    Handshaking,
+    Status,
+    Login,
+    Configuration,
+    Play,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Direction {
+    Clientbound,
+    Serverbound,
+}
+
+impl Direction {
+    pub fn opposite(self) -> Direction {
+        match self {
+            Direction::Clientbound => Direction::Serverbound,
+            Direction::Serverbound => Direction::Clientbound,
+        }
+    }
+}
+
+/// The packet ids which move a connection between states for a single protocol version.
+///
+/// Versions before 1.20.2 (protocol 764) have no configuration state, every configuration id is
+/// `None` and Login Success moves both directions straight into play.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct TransitionIds {
+    pub login_success: i32,
+    pub login_acknowledged: Option<i32>,
+    pub finish_configuration: Option<i32>,
+    pub acknowledge_finish_configuration: Option<i32>,
+    pub start_configuration: Option<i32>,
+    pub acknowledge_configuration: Option<i32>,
+}
+
+impl TransitionIds {
+    /// Protocol versions newer than 1.21.1 (767) are rejected rather than guessed at, construct
+    /// the ids by hand for those.
+    pub fn for_protocol(protocol_version: i32) -> anyhow::Result<TransitionIds> {
+        let (finish, start, acknowledge) = match protocol_version {
+            v if v < 764 => {
+                return Ok(TransitionIds {
+                    login_success: 0x02,
+                    login_acknowledged: None,
+                    finish_configuration: None,
+                    acknowledge_finish_configuration: None,
+                    start_configuration: None,
+                    acknowledge_configuration: None,
+                })
+            }
+            764 => (0x02, 0x65, 0x0B),
+            765 => (0x02, 0x67, 0x0B),
+            766 | 767 => (0x03, 0x69, 0x0C),
+            v => anyhow::bail!(
+                "Unsupported protocol version {} found. No known transition ids",
+                v
+            ),
+        };
+        Ok(TransitionIds {
+            login_success: 0x02,
+            login_acknowledged: Some(0x03),
+            finish_configuration: Some(finish),
+            acknowledge_finish_configuration: Some(finish),
+            start_configuration: Some(start),
+            acknowledge_configuration: Some(acknowledge),
+        })
+    }
+}
+
+/// Tracks the state of both directions of a connection.
+///
+/// The two directions switch at different packets, e.g. after Login Success the server is
+/// already sending configuration packets while the client has yet to send Login Acknowledged.
+#[derive(Clone, Debug)]
+pub struct ConnectionStateMachine {
+    protocol_version: Option<i32>,
+    transitions: Option<TransitionIds>,
+    clientbound: ConnectionState,
+    serverbound: ConnectionState,
+}
+
+impl Default for ConnectionStateMachine {
+    fn default() -> Self {
+        ConnectionStateMachine::new()
+    }
+}
+
+impl ConnectionStateMachine {
+    /// Creates a machine in the handshaking state which picks its transition ids from the
+    /// protocol version announced in the handshake, failing past the status state if
+    /// `TransitionIds::for_protocol` doesn't know that version.
+    pub fn new() -> Self {
+        ConnectionStateMachine {
+            protocol_version: None,
+            transitions: None,
+            clientbound: ConnectionState::Handshaking,
+            serverbound: ConnectionState::Handshaking,
+        }
+    }
+
+    /// Creates a machine which always uses the given transition ids.
+    pub fn with_transitions(transitions: TransitionIds) -> Self {
+        ConnectionStateMachine {
+            transitions: Some(transitions),
+            ..ConnectionStateMachine::new()
+        }
+    }
+
+    pub fn protocol_version(&self) -> Option<i32> {
+        self.protocol_version
+    }
+
+    pub fn state(&self, direction: Direction) -> ConnectionState {
+        match direction {
+            Direction::Clientbound => self.clientbound,
+            Direction::Serverbound => self.serverbound,
+        }
+    }
+
+    pub fn set_state(&mut self, direction: Direction, state: ConnectionState) {
+        match direction {
+            Direction::Clientbound => self.clientbound = state,
+            Direction::Serverbound => self.serverbound = state,
+        }
+    }
+
+    fn transitions(&self) -> anyhow::Result<TransitionIds> {
+        if let Some(transitions) = self.transitions {
+            return Ok(transitions);
+        }
+        match self.protocol_version {
+            Some(protocol_version) => TransitionIds::for_protocol(protocol_version),
+            None => anyhow::bail!("Failed to track connection state, no handshake was observed."),
+        }
+    }
+
+    /// Advances the machine past a packet travelling in `direction`, returning the state the
+    /// packet belonged to.
+    pub fn observe(
+        &mut self,
+        direction: Direction,
+        packet: &RawPacket,
+    ) -> anyhow::Result<ConnectionState> {
+        let state = self.state(direction);
+        let id = *packet.id;
+
+        match (state, direction) {
+            (ConnectionState::Handshaking, Direction::Serverbound) => {
+                if id != 0x00 {
+                    anyhow::bail!("Expected handshake but found packet id {}.", id);
+                }
+                let (protocol_version, next) = read_handshake(packet)?;
+                self.protocol_version = Some(protocol_version);
+                self.clientbound = next;
+                self.serverbound = next;
+                return Ok(state);
+            }
+            (ConnectionState::Handshaking, Direction::Clientbound) => {
+                anyhow::bail!("Found clientbound packet id {} before the handshake.", id);
+            }
+            // status has no transitions, so any protocol version can ping
+            (ConnectionState::Status, _) => return Ok(state),
+            _ => {}
+        }
+
+        let transitions = self.transitions()?;
+        match (state, direction) {
+            (ConnectionState::Login, Direction::Clientbound) if id == transitions.login_success => {
+                if transitions.login_acknowledged.is_some() {
+                    self.clientbound = ConnectionState::Configuration;
+                } else {
+                    self.clientbound = ConnectionState::Play;
+                    self.serverbound = ConnectionState::Play;
+                }
+            }
+            (ConnectionState::Login, Direction::Serverbound)
+                if Some(id) == transitions.login_acknowledged =>
+            {
+                self.serverbound = ConnectionState::Configuration;
+            }
+            (ConnectionState::Configuration, Direction::Clientbound)
+                if Some(id) == transitions.finish_configuration =>
+            {
+                self.clientbound = ConnectionState::Play;
+            }
+            (ConnectionState::Configuration, Direction::Serverbound)
+                if Some(id) == transitions.acknowledge_finish_configuration =>
+            {
+                self.serverbound = ConnectionState::Play;
+            }
+            (ConnectionState::Play, Direction::Clientbound)
+                if Some(id) == transitions.start_configuration =>
+            {
+                self.clientbound = ConnectionState::Configuration;
+            }
+            (ConnectionState::Play, Direction::Serverbound)
+                if Some(id) == transitions.acknowledge_configuration =>
+            {
+                self.serverbound = ConnectionState::Configuration;
+            }
+            _ => {}
+        }
+        Ok(state)
+    }
+}
+
+fn read_handshake(packet: &RawPacket) -> anyhow::Result<(i32, ConnectionState)> {
+    let handshake =
+        Handshake::decode(&mut packet.reader()).context("Failed to decode handshake.")?;
+    Ok((*handshake.protocol_version, handshake.next_state.into()))
+}
+
+/// Reads frames from one side of a connection while keeping a state machine in step.
+pub struct ConnectionReader<R> {
+    reader: R,
+    direction: Direction,
+    machine: ConnectionStateMachine,
+}
+
+impl<R: Read> ConnectionReader<R> {
+    pub fn new(reader: R, direction: Direction) -> Self {
+        ConnectionReader::with_state_machine(reader, direction, ConnectionStateMachine::new())
+    }
+
+    pub fn with_state_machine(
+        reader: R,
+        direction: Direction,
+        machine: ConnectionStateMachine,
+    ) -> Self {
+        ConnectionReader {
+            reader,
+            direction,
+            machine,
+        }
+    }
+
+    /// The direction and state the next frame read will belong to.
+    pub fn next_frame(&self) -> (Direction, ConnectionState) {
+        (self.direction, self.machine.state(self.direction))
+    }
+
+    pub fn state_machine(&self) -> &ConnectionStateMachine {
+        &self.machine
+    }
+
+    /// Packets written in the opposite direction must be reported here to keep the machine
+    /// in step, e.g. a server must observe its own Login Success.
+    pub fn state_machine_mut(&mut self) -> &mut ConnectionStateMachine {
+        &mut self.machine
+    }
+
+    pub fn read_packet(&mut self) -> anyhow::Result<(ConnectionState, RawPacket)> {
+        let packet = RawPacket::decode(&mut self.reader)?;
+        let state = self.machine.observe(self.direction, &packet)?;
+        Ok((state, packet))
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
@@ -1,11 +1,13 @@
+use crate::limits::DecodeLimits;
 use crate::nums::VarInt;
 use anyhow::Context;
 use std::convert::TryFrom;
+use std::io::Read;
 
 #[macro_export]
 macro_rules! auto_string {
     ($name:ident, $size:literal) => {
-        #[derive(Debug)]
+        #[derive(Clone, Debug, Eq, PartialEq, Hash)]
         pub struct $name(String);
 
         impl $crate::strings::McString for $name {
@@ -52,7 +54,7 @@ macro_rules! auto_string {
 
         impl std::fmt::Display for $name {
             fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-                write!(f, "{}", &self)
+                write!(f, "{}", &self.0)
             }
         }
 
@@ -74,23 +76,53 @@ pub trait McString: Sized {
     fn limit() -> VarInt;
 }
 
+fn checked_size<T: McString>(true_size: VarInt) -> anyhow::Result<usize> {
+    if true_size < 0 || true_size > T::limit() * 4 {
+        anyhow::bail!(
+            "Failed to construct string with limit {} with given size {}.",
+            T::limit(),
+            true_size
+        );
+    }
+    Ok(*true_size as usize)
+}
+
 impl<T: McString> crate::encoder::Decodable for T {
     fn decode<R: std::io::Read>(reader: &mut R) -> anyhow::Result<T> {
-        let true_size = VarInt::decode(reader)?;
+        Self::decode_limited(reader, &mut DecodeLimits::default())
+    }
 
-        if true_size > T::limit() * 4 {
-            anyhow::bail!(
-                "Failed to construct string with limit {} with given size {}.",
-                T::limit(),
-                true_size
-            );
-        }
+    fn decode_buf<B: bytes::Buf>(buf: &mut B) -> anyhow::Result<T> {
+        Self::decode_buf_limited(buf, &mut DecodeLimits::default())
+    }
 
-        let mut bytes = vec![0u8; *true_size as usize];
-        reader.read_exact(&mut bytes).context(format!(
-            "Unexpected EOF while decoding string with size {}.",
-            true_size
-        ))?;
+    fn decode_buf_limited<B: bytes::Buf>(
+        buf: &mut B,
+        limits: &mut DecodeLimits,
+    ) -> anyhow::Result<T> {
+        let size = checked_size::<T>(VarInt::decode_buf(buf)?)?;
+        crate::encoder::check_remaining(buf, size, "string")?;
+        limits.string(size)?;
+        let mut bytes = vec![0u8; size];
+        buf.copy_to_slice(&mut bytes);
+        let internal = String::from_utf8(bytes).context("Failed to build UTF-8 encoded string.")?;
+
+        Ok(T::new(internal))
+    }
+
+    fn decode_limited<R: std::io::Read>(
+        reader: &mut R,
+        limits: &mut DecodeLimits,
+    ) -> anyhow::Result<T> {
+        let size = checked_size::<T>(VarInt::decode(reader)?)?;
+        limits.string(size)?;
+
+        // grows with the bytes actually read rather than trusting the size up front
+        let mut bytes = Vec::new();
+        reader.take(size as u64).read_to_end(&mut bytes)?;
+        if bytes.len() != size {
+            anyhow::bail!("Unexpected EOF while decoding string with size {}.", size);
+        }
         let internal = String::from_utf8(bytes).context("Failed to build UTF-8 encoded string.")?;
 
         Ok(T::new(internal))
@@ -117,7 +149,24 @@ impl<T: McString> crate::encoder::Encodable for T {
 
     fn size(&self) -> anyhow::Result<VarInt> {
         let string_len = VarInt::try_from(self.string().len())?;
-        Ok(string_len.size()? + string_len)
+        string_len.size()?.try_add(string_len)
+    }
+
+    fn encode_buf<B: bytes::BufMut>(&self, buf: &mut B) -> anyhow::Result<()> {
+        let bytes = self.string().as_bytes();
+        let length = VarInt::try_from(bytes.len())?;
+        if length > T::limit() {
+            anyhow::bail!(
+                "Failed to encode string with limit {} with given size {}.",
+                T::limit(),
+                bytes.len()
+            );
+        }
+
+        length.encode_buf(buf)?;
+        crate::encoder::check_remaining_mut(buf, bytes.len(), "string")?;
+        buf.put_slice(bytes);
+        Ok(())
     }
 }
 
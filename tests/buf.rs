@@ -0,0 +1,45 @@
+mod common;
+
+use common::round_trip;
+use minecraft_data_types::collections::{Prefixed, Remaining};
+use minecraft_data_types::common::Identifier;
+use minecraft_data_types::encoder::{Decodable, Encodable};
+use minecraft_data_types::legacy::LegacyString;
+use minecraft_data_types::nums::{VarInt, VarLong};
+use minecraft_data_types::packet::RawPacket;
+use minecraft_data_types::packets::handshake::{Handshake, NextState};
+
+#[test]
+fn buf_matches_writer() {
+    for value in [0, 1, 127, 128, 25565, -1, i32::MIN, i32::MAX] {
+        round_trip(&VarInt::from(value));
+    }
+    for value in [0, -1, i64::MIN, i64::MAX] {
+        round_trip(&VarLong::from(value));
+    }
+    round_trip(&0x1234_5678i32);
+    round_trip(&-2.5f64);
+    round_trip(&true);
+    round_trip(&Identifier::from("minecraft:stone"));
+    round_trip(&LegacyString::from("\u{a7}1 legacy"));
+    round_trip(&Remaining(vec![VarInt::from(1), VarInt::from(300)]));
+    round_trip(&Prefixed::<u8, _>::new(vec![Identifier::from("a")]));
+    round_trip(&Handshake {
+        protocol_version: VarInt::from(767),
+        server_address: "localhost".into(),
+        server_port: 25565,
+        next_state: NextState::Login,
+    });
+    round_trip(&RawPacket::new(VarInt::from(0x26), vec![1, 2, 3]));
+}
+
+#[test]
+fn buf_reports_eof() {
+    assert!(VarInt::decode_buf(&mut &[0x80u8, 0x80][..]).is_err());
+    assert!(i64::decode_buf(&mut &[0u8; 7][..]).is_err());
+    assert!(Identifier::decode_buf(&mut &[0x05u8, b'a'][..]).is_err());
+    assert!(RawPacket::decode_buf(&mut &[0x04u8, 0x00, 0x01][..]).is_err());
+
+    let mut full = [0u8; 2];
+    assert!(VarInt::from(25565).encode_buf(&mut &mut full[..]).is_err());
+}
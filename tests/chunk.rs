@@ -0,0 +1,226 @@
+mod common;
+
+use common::{encoded, round_trip};
+use minecraft_data_types::chunk::{
+    Biomes, BlockEntity, BlockStates, ChunkColumn, Palette, PalettedContainer, WorldHeight,
+};
+use minecraft_data_types::encoder::{Decodable, Encodable};
+use minecraft_data_types::nums::VarInt;
+use nbt::Blob;
+
+#[test]
+fn single_valued() {
+    let container = PalettedContainer::<BlockStates>::single(9);
+    assert_eq!(encoded(&container), [0x00, 0x09, 0x00]);
+    assert_eq!(container.get(4095), Some(9));
+    assert_eq!(container.get(4096), None);
+    round_trip(&container);
+}
+
+#[test]
+fn palette_grows_then_goes_direct() {
+    let mut container = PalettedContainer::<BlockStates>::single(0);
+    container.set(0, 1).unwrap();
+    assert_eq!(container.bits_per_entry(), 4);
+    assert_eq!(container.palette(), &Palette::Indirect(vec![0, 1]));
+    assert_eq!(container.data().len(), 256);
+    assert_eq!((container.get(0), container.get(1)), (Some(1), Some(0)));
+    round_trip(&container);
+
+    for id in 2..17 {
+        container.set(id as usize * 100, id).unwrap();
+    }
+    assert_eq!(container.bits_per_entry(), 5);
+    // 12 entries fit a long, leaving the top 4 bits unused
+    assert_eq!(container.data().len(), 342);
+    round_trip(&container);
+
+    for id in 17..300 {
+        container.set(2048 + id as usize, id).unwrap();
+    }
+    assert_eq!(container.bits_per_entry(), 15);
+    assert_eq!(container.palette(), &Palette::Direct);
+    assert_eq!(container.data().len(), 1024);
+    assert_eq!(container.get(0), Some(1));
+    for id in 2..17 {
+        assert_eq!(container.get(id as usize * 100), Some(id));
+    }
+    for id in 17..300 {
+        assert_eq!(container.get(2048 + id as usize), Some(id));
+    }
+    round_trip(&container);
+}
+
+#[test]
+fn entries_do_not_span_longs() {
+    let mut container = PalettedContainer::<BlockStates>::single(0);
+    for id in 1..17 {
+        container.set(4000 + id as usize, id).unwrap();
+    }
+    container.set(11, 16).unwrap();
+    container.set(12, 1).unwrap();
+    assert_eq!(container.bits_per_entry(), 5);
+    assert_eq!(container.data()[0], 16 << 55);
+    assert_eq!(container.data()[1], 1);
+}
+
+#[test]
+fn biome_thresholds() {
+    let mut container = PalettedContainer::<Biomes>::single(0);
+    container.set(1, 1).unwrap();
+    assert_eq!(container.bits_per_entry(), 1);
+    assert_eq!(container.data().len(), 1);
+    for id in 2..9 {
+        container.set(id as usize, id).unwrap();
+    }
+    assert_eq!(container.palette(), &Palette::Direct);
+    assert_eq!(container.bits_per_entry(), 6);
+    round_trip(&container);
+    assert!(container.set(0, 64).is_err());
+    assert!(container.set(64, 0).is_err());
+    assert!(container.set(0, -1).is_err());
+}
+
+#[test]
+fn decodes_like_vanilla() {
+    // blocks pack at least 4 bits whatever the sender says
+    let mut bytes = vec![0x02, 0x02, 0x07, 0x08, 0x80, 0x02];
+    bytes.extend_from_slice(&[0x00; 256 * 8]);
+    bytes[6 + 7] = 0x01;
+    let container = PalettedContainer::<BlockStates>::decode(&mut &bytes[..]).unwrap();
+    assert_eq!(container.bits_per_entry(), 4);
+    assert_eq!((container.get(0), container.get(1)), (Some(8), Some(7)));
+}
+
+#[test]
+fn rejects_malformed() {
+    // wrong number of longs
+    assert!(PalettedContainer::<Biomes>::decode(
+        &mut &[0x00, 0x01, 0x01, 0, 0, 0, 0, 0, 0, 0, 0][..]
+    )
+    .is_err());
+    // an entry past the end of the palette
+    let bytes = [0x01, 0x01, 0x05, 0x01, 0, 0, 0, 0, 0, 0, 0, 0x02];
+    assert!(PalettedContainer::<Biomes>::decode(&mut &bytes[..]).is_err());
+    assert!(PalettedContainer::<Biomes>::decode_buf(&mut &bytes[..]).is_err());
+    // a direct container claiming billions of longs
+    let bytes = [0x0f, 0xff, 0xff, 0xff, 0xff, 0x07];
+    assert!(PalettedContainer::<BlockStates>::decode(&mut &bytes[..]).is_err());
+}
+
+#[test]
+fn column_takes_world_coordinates() {
+    let mut column = ChunkColumn::empty(WorldHeight::OVERWORLD, -2, 3, 1);
+    assert_eq!(column.sections.len(), 24);
+    column.set_block(-17, -64, 48, 5).unwrap();
+    column.set_block(-32, 319, 63, 6).unwrap();
+    column.set_biome(-20, 100, 50, 2).unwrap();
+
+    assert_eq!(column.block(-17, -64, 48), Some(5));
+    assert_eq!(column.block(-32, 319, 63), Some(6));
+    assert_eq!(column.block(-18, -64, 48), Some(0));
+    assert_eq!(column.sections[0].blocks.get(15), Some(5));
+    assert_eq!(column.sections[0].block_count, 1);
+    assert_eq!(column.sections[23].block_count, 1);
+    assert_eq!(column.biome(-17, 103, 48), Some(2));
+    assert_eq!(column.biome(-21, 103, 48), Some(1));
+
+    // outside the column
+    assert_eq!(column.block(0, 0, 48), None);
+    assert_eq!(column.block(-17, -65, 48), None);
+    assert_eq!(column.block(-17, 320, 48), None);
+    assert!(column.set_block(-17, 320, 48, 1).is_err());
+
+    column.set_block(-17, -64, 48, 0).unwrap();
+    assert_eq!(column.sections[0].block_count, 0);
+}
+
+#[test]
+fn column_rejects_extreme_heights() {
+    let mut column = ChunkColumn::empty(WorldHeight::OVERWORLD, 0, 0, 0);
+    for y in [i32::MAX, i32::MIN] {
+        assert_eq!(column.block(0, y, 0), None);
+        assert_eq!(column.biome(0, y, 0), None);
+        assert!(column.block_entity(0, y, 0).is_none());
+        assert!(column.set_block(0, y, 0, 1).is_err());
+        assert!(column.set_biome(0, y, 0, 1).is_err());
+    }
+}
+
+#[test]
+fn column_round_trip() {
+    let mut column = ChunkColumn::empty(WorldHeight::CLASSIC, 7, -1, 0);
+    column
+        .heightmaps
+        .insert("MOTION_BLOCKING", vec![0i64; 37])
+        .unwrap();
+    for y in 0..256 {
+        column
+            .set_block(112 + y % 16, y, -16 + y / 16, y + 1)
+            .unwrap();
+    }
+    let mut sign = Blob::new();
+    sign.insert("is_waxed", 1i8).unwrap();
+    column.block_entities = vec![
+        BlockEntity {
+            x: 15,
+            z: 2,
+            y: 64,
+            kind: VarInt::from(7),
+            data: Some(sign),
+        },
+        BlockEntity {
+            x: 0,
+            z: 0,
+            y: 3,
+            kind: VarInt::from(1),
+            data: None,
+        },
+    ];
+    let bytes = encoded(&column);
+    assert_eq!(
+        ChunkColumn::decode(&mut &bytes[..], WorldHeight::CLASSIC).unwrap(),
+        column
+    );
+    assert_eq!(
+        ChunkColumn::decode_buf(&mut &bytes[..], WorldHeight::CLASSIC).unwrap(),
+        column
+    );
+    assert_eq!(
+        column.block_entity(127, 64, -14).unwrap().kind,
+        VarInt::from(7)
+    );
+    assert!(column.block_entity(127, 65, -14).is_none());
+
+    // a lone end tag for the entity without data ends the packet
+    assert_eq!(bytes.last(), Some(&0x00));
+    // the data holds 24 sections in the overworld, not 16
+    assert!(ChunkColumn::decode(&mut &bytes[..], WorldHeight::OVERWORLD).is_err());
+    assert!(ChunkColumn::decode_buf(&mut &bytes[..], WorldHeight::OVERWORLD).is_err());
+
+    let mut short = ChunkColumn::empty(WorldHeight::CLASSIC, 0, 0, 0);
+    short.sections.pop();
+    assert!(short.encode(&mut Vec::new()).is_err());
+}
+
+#[test]
+fn datapack_world_height() {
+    let height = WorldHeight::new(-128, 512).unwrap();
+    assert_eq!(height.sections(), 32);
+    let mut column = ChunkColumn::empty(height, 0, 0, 0);
+    column.set_block(1, -128, 1, 3).unwrap();
+    column.set_block(1, 383, 1, 4).unwrap();
+    assert!(column.set_block(1, 384, 1, 4).is_err());
+
+    let bytes = encoded(&column);
+    let decoded = ChunkColumn::decode(&mut &bytes[..], height).unwrap();
+    assert_eq!(decoded.height(), height);
+    assert_eq!(decoded.block(1, 383, 1), Some(4));
+    assert!(ChunkColumn::decode(&mut &bytes[..], WorldHeight::OVERWORLD).is_err());
+
+    assert_eq!(WorldHeight::new(-64, 384).unwrap(), WorldHeight::OVERWORLD);
+    assert!(WorldHeight::new(-64, 380).is_err());
+    assert!(WorldHeight::new(-60, 384).is_err());
+    assert!(WorldHeight::new(0, 0).is_err());
+    assert!(WorldHeight::new(2000, 64).is_err());
+}
@@ -0,0 +1,151 @@
+#![cfg(feature = "codec")]
+
+use bytes::BytesMut;
+use futures_util::{SinkExt, StreamExt};
+use minecraft_data_types::codec::{MinecraftCodec, TypedCodec};
+use minecraft_data_types::packet::RawPacket;
+use minecraft_data_types::packets::handshake::{Handshake, NextState};
+use minecraft_data_types::packets::status::StatusRequest;
+use minecraft_data_types::registry::{DecodedPacket, PacketRegistry, ALL_PROTOCOLS};
+use minecraft_data_types::state::{ConnectionState, Direction};
+use std::any::Any;
+use std::sync::Arc;
+use tokio_util::codec::{Decoder, Encoder, Framed};
+
+fn encode(codec: &mut MinecraftCodec, packets: &[RawPacket]) -> BytesMut {
+    let mut bytes = BytesMut::new();
+    for packet in packets {
+        codec.encode(packet, &mut bytes).unwrap();
+    }
+    bytes
+}
+
+fn decode_all(codec: &mut MinecraftCodec, bytes: &[u8]) -> Vec<RawPacket> {
+    // feed a byte at a time to exercise partial frames
+    let mut buffer = BytesMut::new();
+    let mut packets = Vec::new();
+    for byte in bytes {
+        buffer.extend_from_slice(&[*byte]);
+        while let Some(packet) = codec.decode(&mut buffer).unwrap() {
+            packets.push(packet);
+        }
+    }
+    assert!(buffer.is_empty());
+    packets
+}
+
+fn packets() -> Vec<RawPacket> {
+    vec![
+        RawPacket::new(0x01, vec![7; 4]),
+        RawPacket::new(0x22, (0..=255).cycle().take(1000).collect()),
+        RawPacket::new(0x00, vec![]),
+    ]
+}
+
+#[test]
+fn plain_frames() {
+    let bytes = encode(&mut MinecraftCodec::new(), &packets());
+    assert_eq!(&bytes[..6], &[5, 0x01, 7, 7, 7, 7]);
+    assert_eq!(decode_all(&mut MinecraftCodec::new(), &bytes), packets());
+}
+
+#[test]
+fn compressed_frames() {
+    let mut encoder = MinecraftCodec::new();
+    encoder.set_compression_threshold(Some(256));
+    let bytes = encode(&mut encoder, &packets());
+    assert_eq!(&bytes[..3], &[6, 0, 0x01]);
+    assert!(bytes.len() < 1000);
+
+    let mut decoder = MinecraftCodec::new();
+    decoder.set_compression_threshold(Some(256));
+    assert_eq!(decode_all(&mut decoder, &bytes), packets());
+}
+
+#[test]
+fn encrypted_frames() {
+    let secret = [3u8; 16];
+    let mut encoder = MinecraftCodec::new();
+    let mut decoder = MinecraftCodec::new();
+    let plain = encode(&mut encoder, &packets()[..1]);
+    encoder.enable_encryption(&secret).unwrap();
+    encoder.set_compression_threshold(Some(64));
+    let encrypted = encode(&mut encoder, &packets());
+    assert_ne!(&encrypted[..6], &plain[..]);
+
+    let mut buffer = BytesMut::new();
+    buffer.extend_from_slice(&plain);
+    buffer.extend_from_slice(&encrypted);
+    assert_eq!(
+        decoder.decode(&mut buffer).unwrap(),
+        Some(packets()[0].clone())
+    );
+    decoder.enable_encryption(&secret).unwrap();
+    decoder.set_compression_threshold(Some(64));
+    let mut decoded = Vec::new();
+    while let Some(packet) = decoder.decode(&mut buffer).unwrap() {
+        decoded.push(packet);
+    }
+    assert_eq!(decoded, packets());
+    assert!(decoder.enable_encryption(&[0; 5]).is_err());
+}
+
+#[test]
+fn rejects_oversized_frames() {
+    let mut buffer = BytesMut::from(&[0xFF, 0xFF, 0xFF, 0x01][..]);
+    assert!(MinecraftCodec::new().decode(&mut buffer).is_err());
+}
+
+#[tokio::test]
+async fn typed_framed_stream() {
+    let mut registry = PacketRegistry::<Box<dyn Any + Send>>::new();
+    registry.register_any::<Handshake>(
+        ConnectionState::Handshaking,
+        Direction::Serverbound,
+        vec![(ALL_PROTOCOLS, Handshake::ID)],
+    );
+    registry.register_any::<StatusRequest>(
+        ConnectionState::Status,
+        Direction::Serverbound,
+        vec![(ALL_PROTOCOLS, StatusRequest::ID)],
+    );
+
+    let (client, server) = tokio::io::duplex(1024);
+    let mut client = Framed::new(client, MinecraftCodec::new());
+    let mut server = Framed::new(
+        server,
+        TypedCodec::new(Arc::new(registry), Direction::Serverbound),
+    );
+
+    let handshake = Handshake {
+        protocol_version: 767.into(),
+        server_address: "localhost".into(),
+        server_port: 25565,
+        next_state: NextState::Status,
+    };
+    client
+        .send(RawPacket::from_packet(Handshake::ID, &handshake).unwrap())
+        .await
+        .unwrap();
+    client.send(RawPacket::new(0x00, vec![])).await.unwrap();
+    client.send(RawPacket::new(0x05, vec![1])).await.unwrap();
+
+    match server.next().await.unwrap().unwrap() {
+        (ConnectionState::Handshaking, DecodedPacket::Known(packet)) => {
+            assert_eq!(packet.downcast_ref::<Handshake>(), Some(&handshake))
+        }
+        _ => panic!("Expected handshake."),
+    }
+    match server.next().await.unwrap().unwrap() {
+        (ConnectionState::Status, DecodedPacket::Known(packet)) => {
+            assert!(packet.is::<StatusRequest>())
+        }
+        _ => panic!("Expected status request."),
+    }
+    match server.next().await.unwrap().unwrap() {
+        (ConnectionState::Status, DecodedPacket::Unknown(packet)) => {
+            assert_eq!(packet, RawPacket::new(0x05, vec![1]))
+        }
+        _ => panic!("Expected unknown packet."),
+    }
+}
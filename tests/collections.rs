@@ -0,0 +1,156 @@
+mod common;
+
+use common::encoded;
+use minecraft_data_types::collections::{ByteArray, Fixed, Prefixed, PrefixedBytes, Remaining};
+use minecraft_data_types::common::Identifier;
+use minecraft_data_types::encoder::{Decodable, Encodable};
+use minecraft_data_types::nums::VarInt;
+use minecraft_data_types::packet::RawPacket;
+
+#[test]
+fn prefix_widths() {
+    let items = vec![0x11u8, 0x22];
+    assert_eq!(
+        encoded(&Prefixed::<VarInt, _>::new(items.clone())),
+        [2, 0x11, 0x22]
+    );
+    assert_eq!(
+        encoded(&Prefixed::<u8, _>::new(items.clone())),
+        [2, 0x11, 0x22]
+    );
+    assert_eq!(
+        encoded(&Prefixed::<i16, _>::new(items.clone())),
+        [0, 2, 0x11, 0x22]
+    );
+    assert_eq!(
+        encoded(&Prefixed::<i32, _>::new(items)),
+        [0, 0, 0, 2, 0x11, 0x22]
+    );
+
+    assert!(Prefixed::<u8, _>::new(vec![0u8; 256])
+        .encode(&mut Vec::new())
+        .is_err());
+    assert!(Prefixed::<i32, u8>::decode(&mut &[0xff, 0xff, 0xff, 0xfe][..]).is_err());
+    assert!(Prefixed::<i16, u8>::decode_buf(&mut &[0x00, 0x03, 0x01][..]).is_err());
+}
+
+#[test]
+fn fixed_counts() {
+    let fixed = Fixed::<2, i16>::decode(&mut &[0, 1, 0, 2, 0, 3][..]).unwrap();
+    assert_eq!(*fixed, [1, 2]);
+    assert!(Fixed::<3, i16>::from(vec![1, 2]).size().is_err());
+    assert!(Fixed::<3, i16>::decode_buf(&mut &[0, 1, 0, 2][..]).is_err());
+}
+
+#[test]
+fn remaining_ends_with_the_frame() {
+    let packet = RawPacket::new(0x01, vec![0x01, 0xac, 0x02]);
+    let items = packet.decode_packet::<Remaining<VarInt>>().unwrap();
+    assert_eq!(*items, [VarInt::from(1), VarInt::from(300)]);
+    assert!(RawPacket::new(0x01, vec![0x01, 0x80])
+        .decode_packet::<Remaining<VarInt>>()
+        .is_err());
+}
+
+#[test]
+fn remaining_rejects_items_that_consume_nothing() {
+    assert!(Remaining::<[u8; 0]>::decode(&mut &[1, 2][..]).is_err());
+    assert!(Remaining::<[u8; 0]>::decode_buf(&mut &[1, 2][..]).is_err());
+    assert!(Remaining::<[u8; 0]>::decode(&mut &[][..])
+        .unwrap()
+        .is_empty());
+}
+
+/// Counts `write` calls, so bulk paths show up as a single call.
+#[derive(Default)]
+struct CountingWriter {
+    bytes: Vec<u8>,
+    writes: usize,
+}
+
+impl std::io::Write for CountingWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.writes += 1;
+        self.bytes.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn arrays() {
+    let token = [0xde, 0xad, 0xbe, 0xef];
+    assert_eq!(encoded(&token), token);
+    assert_eq!(<[u8; 4]>::decode(&mut &token[..]).unwrap(), token);
+    assert!(<[u8; 5]>::decode(&mut &token[..]).is_err());
+    assert!(<[u8; 5]>::decode_buf(&mut &token[..]).is_err());
+
+    let longs = [1i64, -1];
+    let bytes = encoded(&longs);
+    assert_eq!(bytes[..8], [0, 0, 0, 0, 0, 0, 0, 1]);
+    assert_eq!(<[i64; 2]>::decode_buf(&mut &bytes[..]).unwrap(), longs);
+    assert_eq!(*longs.size().unwrap(), 16);
+
+    let names = [Identifier::from("a"), Identifier::from("bc")];
+    assert_eq!(
+        <[Identifier; 2]>::decode(&mut &encoded(&names)[..]).unwrap(),
+        names
+    );
+}
+
+#[test]
+fn byte_arrays_are_bulk() {
+    let signature = Prefixed::<VarInt, u8>::new(vec![7; 256]);
+    let mut writer = CountingWriter::default();
+    signature.encode(&mut writer).unwrap();
+    assert_eq!(writer.writes, 2);
+    assert_eq!(writer.bytes.len(), 258);
+    assert_eq!(
+        Prefixed::<VarInt, u8>::decode(&mut &writer.bytes[..]).unwrap(),
+        signature
+    );
+
+    let bytes = ByteArray(vec![1, 2, 3]);
+    let mut writer = CountingWriter::default();
+    bytes.encode(&mut writer).unwrap();
+    assert_eq!(writer.writes, 1);
+    assert_eq!(
+        RawPacket::new(0x01, vec![1, 2, 3])
+            .decode_packet::<ByteArray>()
+            .unwrap(),
+        bytes
+    );
+
+    let prefixed = PrefixedBytes::<VarInt>::new(vec![7; 256]);
+    let mut writer = CountingWriter::default();
+    prefixed.encode(&mut writer).unwrap();
+    assert_eq!(writer.bytes, encoded(&signature));
+    assert_eq!(prefixed.size().unwrap(), VarInt::from(258));
+    assert_eq!(
+        PrefixedBytes::<VarInt>::decode(&mut &writer.bytes[..]).unwrap(),
+        prefixed
+    );
+    assert_eq!(
+        PrefixedBytes::<VarInt>::decode_buf(&mut &writer.bytes[..]).unwrap(),
+        prefixed
+    );
+}
+
+#[test]
+fn prefixed_bytes_honour_their_length() {
+    // a frame whose prefix disagrees with the bytes after it, which reading the rest of the
+    // frame after a VarInt would accept
+    let long = RawPacket::new(0x01, vec![2, 1, 2, 3]);
+    assert!(long.decode_packet::<PrefixedBytes<VarInt>>().is_err());
+    assert_eq!(
+        *PrefixedBytes::<VarInt>::decode(&mut &long.data[..]).unwrap(),
+        [1, 2]
+    );
+
+    assert!(PrefixedBytes::<VarInt>::decode(&mut &[4, 1, 2, 3][..]).is_err());
+    assert!(PrefixedBytes::<VarInt>::decode_buf(&mut &[4, 1, 2, 3][..]).is_err());
+    assert!(PrefixedBytes::<VarInt>::decode(&mut &[0xff, 0xff, 0xff, 0xff, 0x07][..]).is_err());
+}
@@ -0,0 +1,34 @@
+//! Helpers shared by the integration tests, each of which only uses some of them.
+#![allow(dead_code)]
+
+use minecraft_data_types::encoder::{Decodable, Encodable};
+use std::fmt::Debug;
+
+/// Encodes `value`, checking `size` matches the written length.
+pub fn encoded<T: Encodable>(value: &T) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    value.encode(&mut bytes).unwrap();
+    assert_eq!(*value.size().unwrap() as usize, bytes.len());
+    bytes
+}
+
+/// Checks every path agrees: `encode`, `encode_buf` and `encode_to_bytes` write the same bytes,
+/// and both decoders read exactly those bytes back to the original value.
+pub fn round_trip<T: Decodable + Encodable + PartialEq + Debug>(value: &T) {
+    let bytes = encoded(value);
+
+    let mut buf = Vec::new();
+    value.encode_buf(&mut buf).unwrap();
+    assert_eq!(buf, bytes);
+    let exact = value.encode_to_bytes().unwrap();
+    assert_eq!(&exact[..], &bytes[..]);
+    assert_eq!(exact.capacity(), bytes.len());
+
+    let mut reader = &bytes[..];
+    assert_eq!(&T::decode(&mut reader).unwrap(), value);
+    assert!(reader.is_empty());
+
+    let mut reader = &bytes[..];
+    assert_eq!(&T::decode_buf(&mut reader).unwrap(), value);
+    assert!(reader.is_empty());
+}
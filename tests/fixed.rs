@@ -0,0 +1,71 @@
+mod common;
+
+use common::encoded;
+use minecraft_data_types::encoder::Decodable;
+use minecraft_data_types::fixed::{FixedPoint5, PositionDelta};
+
+#[test]
+fn fixed_point_floors() {
+    assert_eq!(FixedPoint5::<i32>::from_f64(1.5).unwrap(), FixedPoint5(48));
+    assert_eq!(
+        FixedPoint5::<i32>::from_f64(-0.01).unwrap(),
+        FixedPoint5(-1)
+    );
+    assert_eq!(FixedPoint5::<i32>::from_f64(0.03).unwrap(), FixedPoint5(0));
+    assert_eq!(FixedPoint5(-48i32).to_f64(), -1.5);
+    assert_eq!(
+        FixedPoint5::<i8>::from_f64(3.96875).unwrap(),
+        FixedPoint5(127)
+    );
+}
+
+#[test]
+fn fixed_point_rejects_out_of_range() {
+    assert!(FixedPoint5::<i8>::from_f64(4.0).is_err());
+    assert!(FixedPoint5::<i8>::from_f64(-4.0).is_ok());
+    assert!(FixedPoint5::<i32>::from_f64(f64::NAN).is_err());
+    assert!(FixedPoint5::<i32>::from_f64(f64::INFINITY).is_err());
+    assert!(FixedPoint5::<i32>::from_f64(1e9).is_err());
+}
+
+#[test]
+fn fixed_point_encodes_as_raw() {
+    let position = FixedPoint5(-48i32);
+    let bytes = encoded(&position);
+    assert_eq!(bytes, [0xff, 0xff, 0xff, 0xd0]);
+    assert_eq!(
+        FixedPoint5::<i32>::decode(&mut &bytes[..]).unwrap(),
+        position
+    );
+    assert_eq!(encoded(&FixedPoint5(-1i8)), [0xff]);
+}
+
+#[test]
+fn position_deltas() {
+    assert_eq!(
+        PositionDelta::between(10.0, 10.5).unwrap(),
+        PositionDelta(2048)
+    );
+    assert_eq!(
+        PositionDelta::between(0.5, -0.5).unwrap(),
+        PositionDelta(-4096)
+    );
+    assert!(PositionDelta::between(0.0, 8.0).is_err());
+    assert!(PositionDelta::between(0.0, f64::NAN).is_err());
+    assert_eq!(PositionDelta(-2048).to_f64(), -0.5);
+    assert_eq!(PositionDelta::from_f64(-0.5).unwrap(), PositionDelta(-2048));
+
+    // a pre-1.9 move of 1/32 of a block translates exactly
+    assert_eq!(PositionDelta::from(FixedPoint5(-1i8)), PositionDelta(-128));
+    assert_eq!(
+        PositionDelta::from(FixedPoint5(127i8)).to_f64(),
+        FixedPoint5(127i8).to_f64()
+    );
+
+    let bytes = encoded(&PositionDelta(2048));
+    assert_eq!(bytes, [0x08, 0x00]);
+    assert_eq!(
+        PositionDelta::decode(&mut &bytes[..]).unwrap(),
+        PositionDelta(2048)
+    );
+}
@@ -0,0 +1,54 @@
+use minecraft_data_types::collections::Prefixed;
+use minecraft_data_types::common::Identifier;
+use minecraft_data_types::encoder::Decodable;
+use minecraft_data_types::nums::VarInt;
+use nbt::Blob;
+
+/// A root compound holding one unnamed tag with the given id and payload.
+fn nbt_root(tag: u8, payload: &[u8]) -> Vec<u8> {
+    let mut bytes = vec![0x0a, 0x00, 0x00, tag, 0x00, 0x00];
+    bytes.extend_from_slice(payload);
+    bytes.push(0x00);
+    bytes
+}
+
+fn rejects<T: Decodable>(bytes: &[u8]) {
+    assert!(T::decode(&mut &bytes[..]).is_err());
+    assert!(T::decode_buf(&mut &bytes[..]).is_err());
+}
+
+#[test]
+fn rejects_forged_lengths() {
+    rejects::<Identifier>(&[0xff, 0xff, 0xff, 0xff, 0x0f]);
+    rejects::<Identifier>(&[0xff, 0xff, 0xff, 0xff, 0x07, b'a']);
+    rejects::<Prefixed<VarInt, VarInt>>(&[0xff, 0xff, 0xff, 0xff, 0x07, 0x01]);
+    rejects::<Prefixed<VarInt, Identifier>>(&[0xff, 0xff, 0xff, 0xff, 0x0f]);
+
+    // byte, int and long arrays claiming i32::MAX elements, then a negative length
+    for tag in [0x07, 0x0b, 0x0c] {
+        rejects::<Blob>(&nbt_root(tag, &[0x7f, 0xff, 0xff, 0xff]));
+        rejects::<Blob>(&nbt_root(tag, &[0xff, 0xff, 0xff, 0xff]));
+    }
+    // lists of end tags carry no bytes per element
+    rejects::<Blob>(&nbt_root(0x09, &[0x00, 0x7f, 0xff, 0xff, 0xff]));
+    rejects::<Blob>(&nbt_root(0x09, &[0x0a, 0x7f, 0xff, 0xff, 0xff]));
+    rejects::<Blob>(&nbt_root(0x0d, &[]));
+}
+
+#[test]
+fn rejects_deep_nbt() {
+    // a list of lists of lists..., each holding one element
+    let mut bytes = vec![0x0a, 0x00, 0x00, 0x09, 0x00, 0x00];
+    for _ in 0..100_000 {
+        bytes.extend_from_slice(&[0x09, 0x00, 0x00, 0x00, 0x01]);
+    }
+    let error = Blob::decode(&mut &bytes[..]).unwrap_err();
+    assert!(error.to_string().contains("nested deeper"));
+
+    let mut bytes = vec![0x0a, 0x00, 0x00];
+    for _ in 0..511 {
+        bytes.extend_from_slice(&[0x0a, 0x00, 0x00]);
+    }
+    bytes.extend_from_slice(&[0x00; 512]);
+    assert!(Blob::decode(&mut &bytes[..]).is_ok());
+}
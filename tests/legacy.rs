@@ -0,0 +1,110 @@
+use minecraft_data_types::common::Chat;
+use minecraft_data_types::encoder::{Decodable, Encodable};
+use minecraft_data_types::legacy::*;
+use minecraft_data_types::packets::status::{ServerStatus, StatusPlayers, StatusVersion};
+use minecraft_data_types::ping::StatusResponder;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+fn status() -> ServerStatus {
+    ServerStatus {
+        version: StatusVersion {
+            name: "1.6.4".to_string(),
+            protocol: 78,
+        },
+        players: Some(StatusPlayers {
+            max: 20,
+            online: 1,
+            sample: vec![],
+        }),
+        description: Chat::from(r#"{"text":"A ","extra":[{"text":"Minecraft Server"}]}"#),
+        favicon: None,
+        enforces_secure_chat: None,
+    }
+}
+
+#[test]
+fn utf16_round_trip() {
+    let bytes = encode_utf16_be("\u{a7}1 \u{1F600}");
+    assert_eq!(&bytes[..4], &[0x00, 0xA7, 0x00, 0x31]);
+    assert_eq!(decode_utf16_be(&bytes).unwrap(), "\u{a7}1 \u{1F600}");
+    assert!(decode_utf16_be(&[0x00]).is_err());
+}
+
+#[test]
+fn kick_message_round_trip() {
+    let response = LegacyPingResponse::from_status(&status());
+    assert_eq!(response.motd, "A Minecraft Server");
+    assert_eq!(
+        response.kick_message(),
+        "\u{a7}1\u{0}78\u{0}1.6.4\u{0}A Minecraft Server\u{0}1\u{0}20"
+    );
+
+    let mut bytes = Vec::new();
+    response.encode(&mut bytes).unwrap();
+    assert_eq!(&bytes[..5], &[0xFF, 0x00, 0x23, 0x00, 0xA7]);
+    assert_eq!(response.size().unwrap(), bytes.len() as i32);
+    assert_eq!(
+        LegacyPingResponse::decode(&mut std::io::Cursor::new(bytes)).unwrap(),
+        response
+    );
+}
+
+#[test]
+fn ping_host_round_trip() {
+    let request = LegacyPingRequest::V1_6 {
+        protocol_version: 78,
+        hostname: "localhost".to_string(),
+        port: 25565,
+    };
+    let mut bytes = Vec::new();
+    request.encode(&mut bytes).unwrap();
+    assert_eq!(&bytes[..3], &[0xFE, 0x01, 0xFA]);
+    assert_eq!(
+        LegacyPingRequest::decode_ping_host(&mut std::io::Cursor::new(&bytes[2..])).unwrap(),
+        request
+    );
+}
+
+async fn legacy_exchange(request: LegacyPingRequest) -> Vec<u8> {
+    let (mut client, mut server) = tokio::io::duplex(1024);
+    let mut responder = StatusResponder::new(status());
+    responder.legacy_grace = Duration::from_millis(20);
+    let server = tokio::spawn(async move { responder.serve(&mut server).await });
+
+    let mut bytes = Vec::new();
+    request.encode(&mut bytes).unwrap();
+    client.write_all(&bytes).await.unwrap();
+    assert!(server.await.unwrap().unwrap().is_none());
+
+    let mut response = Vec::new();
+    client.read_to_end(&mut response).await.unwrap();
+    response
+}
+
+#[tokio::test]
+async fn responder_answers_legacy_pings() {
+    let expected = LegacyPingResponse::from_status(&status());
+
+    let response = legacy_exchange(LegacyPingRequest::V1_6 {
+        protocol_version: 78,
+        hostname: "localhost".to_string(),
+        port: 25565,
+    })
+    .await;
+    assert_eq!(
+        LegacyPingResponse::decode(&mut std::io::Cursor::new(response)).unwrap(),
+        expected
+    );
+
+    let response = legacy_exchange(LegacyPingRequest::V1_4).await;
+    assert_eq!(
+        LegacyPingResponse::decode(&mut std::io::Cursor::new(response)).unwrap(),
+        expected
+    );
+
+    let response = legacy_exchange(LegacyPingRequest::Beta).await;
+    let beta = LegacyPingResponse::decode(&mut std::io::Cursor::new(response)).unwrap();
+    assert_eq!(beta.motd, "A Minecraft Server");
+    assert_eq!((beta.online, beta.max), (1, 20));
+}
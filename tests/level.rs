@@ -0,0 +1,274 @@
+#![cfg(feature = "level")]
+
+use minecraft_data_types::common::Identifier;
+use minecraft_data_types::level::{
+    read_gzip_nbt, write_gzip_nbt, ItemData, LevelView, PlayerView, SavedItem,
+};
+use nbt::{Blob, Map, Value};
+use std::path::PathBuf;
+use uuid::Uuid;
+
+fn temp_directory(name: &str) -> PathBuf {
+    let directory = std::env::temp_dir().join(format!("level-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&directory);
+    std::fs::create_dir_all(&directory).unwrap();
+    directory
+}
+
+fn compound(fields: Vec<(&str, Value)>) -> Map<String, Value> {
+    fields
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value))
+        .collect()
+}
+
+fn item(slot: i8, id: &str, count: i8) -> Value {
+    Value::Compound(compound(vec![
+        ("Slot", Value::Byte(slot)),
+        ("id", Value::String(id.to_string())),
+        ("Count", Value::Byte(count)),
+    ]))
+}
+
+fn player() -> Blob {
+    let mut blob = Blob::new();
+    blob.insert("DataVersion", 3465).unwrap();
+    blob.insert(
+        "UUID",
+        Value::IntArray(vec![0x0123_4567, -0x7654_3211, 0x1032_5476, -1]),
+    )
+    .unwrap();
+    blob.insert("Dimension", "minecraft:the_nether").unwrap();
+    blob.insert(
+        "Pos",
+        Value::List(vec![
+            Value::Double(12.5),
+            Value::Double(-60.0),
+            Value::Double(-3.25),
+        ]),
+    )
+    .unwrap();
+    blob.insert(
+        "Rotation",
+        Value::List(vec![Value::Float(90.0), Value::Float(-15.5)]),
+    )
+    .unwrap();
+    blob.insert("SelectedItemSlot", 4).unwrap();
+    blob.insert(
+        "Inventory",
+        Value::List(vec![
+            item(0, "minecraft:stone", 64),
+            Value::Compound(compound(vec![
+                ("Slot", Value::Byte(100)),
+                ("id", Value::String("minecraft:diamond_boots".to_string())),
+                ("Count", Value::Byte(1)),
+                (
+                    "tag",
+                    Value::Compound(compound(vec![("Damage", Value::Int(3))])),
+                ),
+            ])),
+        ]),
+    )
+    .unwrap();
+    blob.insert(
+        "EnderItems",
+        Value::List(vec![item(26, "minecraft:elytra", 1)]),
+    )
+    .unwrap();
+    blob
+}
+
+#[test]
+fn gzip_round_trip() {
+    let directory = temp_directory("gzip");
+    let path = directory.join("player.dat");
+    let blob = player();
+    write_gzip_nbt(&path, &blob).unwrap();
+    assert!(!directory.join("player.dat.tmp").exists());
+    assert_eq!(&std::fs::read(&path).unwrap()[..2], &[0x1f, 0x8b]);
+    assert_eq!(read_gzip_nbt(&path).unwrap(), blob);
+
+    // overwriting replaces the file
+    let mut other = Blob::new();
+    other.insert("DataVersion", 1).unwrap();
+    write_gzip_nbt(&path, &other).unwrap();
+    assert_eq!(read_gzip_nbt(&path).unwrap(), other);
+    std::fs::remove_dir_all(directory).unwrap();
+}
+
+#[test]
+fn gzip_errors() {
+    let directory = temp_directory("errors");
+    assert!(read_gzip_nbt(directory.join("missing.dat")).is_err());
+    std::fs::write(directory.join("plain.dat"), [10, 0, 0, 0]).unwrap();
+    assert!(read_gzip_nbt(directory.join("plain.dat")).is_err());
+    std::fs::remove_dir_all(directory).unwrap();
+}
+
+#[test]
+fn player_view() {
+    let blob = player();
+    let player = PlayerView::new(&blob);
+    assert_eq!(player.data_version(), Some(3465));
+    assert_eq!(
+        player.uuid(),
+        Some(Uuid::from_u128(0x01234567_89abcdef_10325476_ffffffff))
+    );
+    assert_eq!(
+        player.dimension(),
+        Some(Identifier::from("minecraft:the_nether"))
+    );
+    assert_eq!(player.position(), Some((12.5, -60.0, -3.25)));
+    assert_eq!(player.rotation(), Some((90.0, -15.5)));
+    assert_eq!(player.selected_slot(), Some(4));
+
+    let inventory = player.inventory().unwrap();
+    assert_eq!(inventory.len(), 2);
+    assert_eq!(
+        inventory[0],
+        SavedItem {
+            slot: 0,
+            id: Identifier::from("minecraft:stone"),
+            count: 64,
+            data: ItemData::Tag(None),
+        }
+    );
+    assert_eq!(
+        inventory[1].data,
+        ItemData::Tag(Some(compound(vec![("Damage", Value::Int(3))])))
+    );
+    let ender_items = player.ender_items().unwrap();
+    assert_eq!(ender_items.len(), 1);
+    assert_eq!(ender_items[0].slot, 26);
+}
+
+fn with_items(items: Vec<Value>) -> Blob {
+    let mut blob = Blob::new();
+    blob.insert("Inventory", Value::List(items)).unwrap();
+    blob
+}
+
+#[test]
+fn player_inventory_with_components() {
+    let components = compound(vec![("minecraft:damage", Value::Int(3))]);
+    let blob = with_items(vec![
+        Value::Compound(compound(vec![
+            ("Slot", Value::Byte(100)),
+            ("id", Value::String("minecraft:diamond_boots".to_string())),
+            ("count", Value::Int(1)),
+            ("components", Value::Compound(components.clone())),
+        ])),
+        Value::Compound(compound(vec![
+            ("Slot", Value::Byte(0)),
+            ("id", Value::String("minecraft:stone".to_string())),
+            ("count", Value::Int(99)),
+        ])),
+    ]);
+    let inventory = PlayerView::new(&blob).inventory().unwrap();
+    assert_eq!(
+        inventory[0],
+        SavedItem {
+            slot: 100,
+            id: Identifier::from("minecraft:diamond_boots"),
+            count: 1,
+            data: ItemData::Components(Some(components)),
+        }
+    );
+    assert_eq!(inventory[1].count, 99);
+    assert_eq!(inventory[1].data, ItemData::Components(None));
+}
+
+#[test]
+fn player_inventory_errors() {
+    let no_count = with_items(vec![Value::Compound(compound(vec![
+        ("Slot", Value::Byte(1)),
+        ("id", Value::String("minecraft:stone".to_string())),
+    ]))]);
+    assert!(PlayerView::new(&no_count).inventory().is_err());
+
+    // a tag only goes with the byte Count of the old layout
+    let mixed = with_items(vec![Value::Compound(compound(vec![
+        ("Slot", Value::Byte(0)),
+        ("id", Value::String("minecraft:stone".to_string())),
+        ("count", Value::Int(1)),
+        ("tag", Value::Compound(Map::new())),
+    ]))]);
+    assert!(PlayerView::new(&mixed).inventory().is_err());
+
+    let no_id = with_items(vec![Value::Compound(compound(vec![
+        ("Slot", Value::Byte(0)),
+        ("Count", Value::Byte(1)),
+    ]))]);
+    assert!(PlayerView::new(&no_id).inventory().is_err());
+}
+
+#[test]
+fn player_view_legacy_uuid_and_missing_fields() {
+    let mut blob = Blob::new();
+    blob.insert("UUIDMost", 0x0123_4567_89ab_cdefi64).unwrap();
+    blob.insert("UUIDLeast", -2i64).unwrap();
+    blob.insert("Pos", Value::List(vec![Value::Double(1.0)]))
+        .unwrap();
+    let player = PlayerView::new(&blob);
+    assert_eq!(
+        player.uuid(),
+        Some(Uuid::from_u128(0x01234567_89abcdef_ffffffff_fffffffe))
+    );
+    assert_eq!(player.position(), None);
+    assert_eq!(player.rotation(), None);
+    assert_eq!(player.dimension(), None);
+    assert!(player.inventory().unwrap().is_empty());
+}
+
+#[test]
+fn level_view() {
+    let mut blob = Blob::new();
+    blob.insert(
+        "Data",
+        Value::Compound(compound(vec![
+            ("DataVersion", Value::Int(3465)),
+            ("LevelName", Value::String("New World".to_string())),
+            (
+                "Version",
+                Value::Compound(compound(vec![(
+                    "Name",
+                    Value::String("1.20.1".to_string()),
+                )])),
+            ),
+            ("SpawnX", Value::Int(-16)),
+            ("SpawnY", Value::Int(70)),
+            ("SpawnZ", Value::Int(240)),
+            ("SpawnAngle", Value::Float(0.0)),
+            (
+                "GameRules",
+                Value::Compound(compound(vec![
+                    ("doDaylightCycle", Value::String("false".to_string())),
+                    ("randomTickSpeed", Value::String("3".to_string())),
+                ])),
+            ),
+            (
+                "Player",
+                Value::Compound(compound(vec![("SelectedItemSlot", Value::Int(8))])),
+            ),
+        ])),
+    )
+    .unwrap();
+
+    let level = LevelView::new(&blob).unwrap();
+    assert_eq!(level.data_version(), Some(3465));
+    assert_eq!(level.level_name(), Some("New World"));
+    assert_eq!(level.version_name(), Some("1.20.1"));
+    assert_eq!(level.spawn(), Some((-16, 70, 240)));
+    assert_eq!(level.spawn_angle(), Some(0.0));
+    assert_eq!(level.game_rule("randomTickSpeed"), Some("3"));
+    assert_eq!(level.game_rule("keepInventory"), None);
+    let mut rules: Vec<_> = level.game_rules().collect();
+    rules.sort_unstable();
+    assert_eq!(
+        rules,
+        vec![("doDaylightCycle", "false"), ("randomTickSpeed", "3")]
+    );
+    assert_eq!(level.player().unwrap().selected_slot(), Some(8));
+
+    assert!(LevelView::new(&Blob::new()).is_err());
+}
@@ -0,0 +1,116 @@
+mod common;
+
+use common::encoded;
+use minecraft_data_types::chunk::{ChunkColumn, WorldHeight};
+use minecraft_data_types::encoder::{Decodable, Encodable};
+use minecraft_data_types::light::{BitSet, LightData, NibbleArray};
+use minecraft_data_types::nums::VarInt;
+
+#[test]
+fn bit_set_matches_java() {
+    let mut bits = BitSet::new();
+    bits.set(0, true);
+    bits.set(65, true);
+    assert_eq!(bits.longs(), [1, 2]);
+    assert_eq!(
+        encoded(&bits),
+        [0x02, 0, 0, 0, 0, 0, 0, 0, 0x01, 0, 0, 0, 0, 0, 0, 0, 0x02]
+    );
+    assert!(bits.get(65) && !bits.get(64) && !bits.get(1000));
+
+    // trailing zero longs are trimmed like toLongArray
+    bits.set(65, false);
+    assert_eq!(bits.longs(), [1]);
+    let padded = [0x02, 0, 0, 0, 0, 0, 0, 0, 0x01, 0, 0, 0, 0, 0, 0, 0, 0x00];
+    assert_eq!(BitSet::decode(&mut &padded[..]).unwrap(), bits);
+}
+
+#[test]
+fn nibbles_pack_low_first() {
+    let mut light = NibbleArray::default();
+    light.set(0, 0, 0, 15);
+    light.set(1, 0, 0, 3);
+    light.set(15, 15, 15, 7);
+    assert_eq!(light.0[0], 0x3f);
+    assert_eq!(light.0[2047], 0x70);
+    assert_eq!((light.get(1, 0, 0), light.get(-1, -1, -1)), (3, 7));
+    assert_eq!(NibbleArray::filled(15).get(4, 5, 6), 15);
+    assert!(NibbleArray::default().is_empty());
+}
+
+#[test]
+fn layers_keep_arrays_in_mask_order() {
+    let mut light = LightData::default();
+    light.sky.set(5, NibbleArray::filled(15));
+    light.sky.set(1, NibbleArray::filled(3));
+    light.sky.set(3, NibbleArray::default());
+    assert_eq!(light.sky.mask.longs(), [0b100010]);
+    assert_eq!(light.sky.empty_mask.longs(), [0b1000]);
+    assert_eq!(light.sky.arrays[0].get(0, 0, 0), 3);
+    assert_eq!(light.sky.get(5).unwrap().get(0, 0, 0), 15);
+    assert!(light.sky.get(3).is_none());
+
+    assert_eq!(light.sky.remove(1).unwrap().get(0, 0, 0), 3);
+    light.sky.remove(3);
+    assert_eq!(light.sky.mask.longs(), [0b100000]);
+    assert!(light.sky.empty_mask.longs().is_empty());
+    assert_eq!(light.sky.get(5).unwrap().get(0, 0, 0), 15);
+}
+
+#[test]
+fn light_round_trip() {
+    let mut light = LightData::default();
+    for section in 0..18 {
+        light.sky.set(section, NibbleArray::filled(15));
+    }
+    let mut torch = NibbleArray::default();
+    torch.set(8, 8, 8, 14);
+    light.block.set(4, torch);
+    light.block.set(5, NibbleArray::default());
+
+    let bytes = encoded(&light);
+    assert_eq!(LightData::decode(&mut &bytes[..]).unwrap(), light);
+    assert_eq!(LightData::decode_buf(&mut &bytes[..]).unwrap(), light);
+
+    // Chunk Data and Update Light is the column followed by the light
+    let packet = (
+        ChunkColumn::empty(WorldHeight::CLASSIC, 1, 2, 0),
+        light.clone(),
+    );
+    let bytes = encoded(&packet);
+    let mut reader = &bytes[..];
+    assert_eq!(
+        (
+            ChunkColumn::decode(&mut reader, WorldHeight::CLASSIC).unwrap(),
+            LightData::decode(&mut reader).unwrap()
+        ),
+        packet
+    );
+    assert!(reader.is_empty());
+
+    let update = (VarInt::from(1), VarInt::from(2), light);
+    let bytes = encoded(&update);
+    assert_eq!(
+        <(VarInt, VarInt, LightData)>::decode_buf(&mut &bytes[..]).unwrap(),
+        update
+    );
+}
+
+#[test]
+fn rejects_malformed_light() {
+    // a mask bit with no array behind it
+    let bytes = [0x01, 1, 0, 0, 0, 0, 0, 0, 0, 0x00, 0x00, 0x00, 0x00, 0x00];
+    assert!(LightData::decode(&mut &bytes[..]).is_err());
+    // an array of the wrong length
+    let mut bytes = vec![
+        0x01, 0, 0, 0, 0, 0, 0, 0, 1, 0x00, 0x00, 0x00, 0x01, 0x80, 0x08,
+    ];
+    bytes.extend_from_slice(&[0; 1024]);
+    bytes.push(0x00);
+    assert!(LightData::decode(&mut &bytes[..]).is_err());
+    assert!(LightData::decode_buf(&mut &bytes[..]).is_err());
+
+    let mut light = LightData::default();
+    light.block.arrays.push(NibbleArray::default());
+    assert!(light.encode(&mut Vec::new()).is_err());
+}
@@ -0,0 +1,158 @@
+mod common;
+
+use common::encoded;
+use minecraft_data_types::collections::{ByteArray, Fixed, Prefixed, Remaining};
+use minecraft_data_types::common::Identifier;
+use minecraft_data_types::encoder::Decodable;
+use minecraft_data_types::limits::{DecodeLimits, LimitError};
+use minecraft_data_types::nums::VarInt;
+use minecraft_data_types::packet::RawPacket;
+use nbt::Blob;
+
+fn limit_error<T: Decodable + std::fmt::Debug>(
+    bytes: &[u8],
+    limits: &mut DecodeLimits,
+) -> LimitError {
+    let error = T::decode_limited(&mut &bytes[..], limits).unwrap_err();
+    *error
+        .downcast_ref::<LimitError>()
+        .unwrap_or_else(|| panic!("expected a limit error, found {}", error))
+}
+
+#[test]
+fn reports_each_limit() {
+    let mut limits = DecodeLimits::new();
+    limits.max_collection_length = 4;
+    limits.max_string_bytes = 8;
+    limits.max_nbt_depth = 2;
+    limits.max_nbt_bytes = 32;
+
+    let list = Prefixed::<VarInt, _>::new(vec![VarInt::from(0); 5]);
+    assert_eq!(
+        limit_error::<Prefixed<VarInt, VarInt>>(&encoded(&list), &mut limits),
+        LimitError::CollectionLength {
+            length: 5,
+            limit: 4
+        }
+    );
+    assert_eq!(
+        limit_error::<Identifier>(&encoded(&Identifier::from("minecraft:stone")), &mut limits),
+        LimitError::StringBytes {
+            length: 15,
+            limit: 8
+        }
+    );
+
+    let mut nested = Blob::new();
+    nested
+        .insert("a", nbt::Value::List(vec![nbt::Value::List(Vec::new())]))
+        .unwrap();
+    assert_eq!(
+        limit_error::<Blob>(&encoded(&nested), &mut limits),
+        LimitError::NbtDepth { limit: 2 }
+    );
+
+    let mut large = Blob::new();
+    large.insert("a", vec![0i64; 4]).unwrap();
+    assert_eq!(
+        limit_error::<Blob>(&encoded(&large), &mut limits),
+        LimitError::NbtBytes { limit: 32 }
+    );
+}
+
+#[test]
+fn budget_spans_the_packet() {
+    let strings = Prefixed::<VarInt, _>::new(vec![Identifier::from("minecraft:stone"); 3]);
+    let packet = RawPacket::from_packet(0x01, &strings).unwrap();
+    let mut limits = DecodeLimits::new();
+    limits.max_packet_allocation = 40;
+
+    // the items are charged up front, then each string as it is read
+    let items = std::mem::size_of::<Identifier>() * 3;
+    let error = packet
+        .decode_packet_limited::<Prefixed<VarInt, Identifier>>(&limits)
+        .unwrap_err();
+    assert_eq!(
+        error.downcast_ref::<LimitError>(),
+        Some(&LimitError::PacketAllocation {
+            requested: items,
+            remaining: 40
+        })
+    );
+
+    limits.max_packet_allocation = items + 45;
+    for _ in 0..2 {
+        assert_eq!(
+            packet
+                .decode_packet_limited::<Prefixed<VarInt, Identifier>>(&limits)
+                .unwrap(),
+            strings
+        );
+    }
+    limits.max_packet_allocation = items + 44;
+    assert!(packet
+        .decode_packet_limited::<Prefixed<VarInt, Identifier>>(&limits)
+        .is_err());
+}
+
+#[test]
+fn remaining_bytes_stop_at_the_budget() {
+    let mut limits = DecodeLimits::new();
+    limits.max_packet_allocation = 1024;
+    limits.allocate(24).unwrap();
+
+    // an endless reader fails once past the budget instead of being read to its end
+    let error = ByteArray::decode_limited(&mut std::io::repeat(7), &mut limits).unwrap_err();
+    assert_eq!(
+        error.downcast_ref::<LimitError>(),
+        Some(&LimitError::PacketAllocation {
+            requested: 1001,
+            remaining: 1000,
+        })
+    );
+    limits.reset();
+    assert!(Remaining::<u8>::decode_limited(&mut std::io::repeat(7), &mut limits).is_err());
+
+    // exactly the budget still fits
+    limits.reset();
+    let bytes = ByteArray::decode_limited(&mut &[7u8; 1024][..], &mut limits).unwrap();
+    assert_eq!(bytes.len(), 1024);
+}
+
+#[test]
+fn buf_paths_take_the_callers_limits() {
+    let bytes = encoded(&Prefixed::<VarInt, _>::new(vec![1u8; 8]));
+
+    // a lowered limit is enforced
+    let mut limits = DecodeLimits::new();
+    limits.max_collection_length = 4;
+    let error =
+        Prefixed::<VarInt, u8>::decode_buf_limited(&mut &bytes[..], &mut limits).unwrap_err();
+    assert_eq!(
+        error.downcast_ref::<LimitError>(),
+        Some(&LimitError::CollectionLength {
+            length: 8,
+            limit: 4,
+        })
+    );
+    // and reaches collections nested in others
+    let nested = encoded(&Fixed::<1, _>(vec![Prefixed::<VarInt, _>::new(vec![
+        Identifier::from("a");
+        8
+    ])]));
+    assert!(
+        Fixed::<1, Prefixed<VarInt, Identifier>>::decode_buf_limited(&mut &nested[..], &mut limits)
+            .is_err()
+    );
+    assert!(Fixed::<1, Prefixed<VarInt, Identifier>>::decode_buf(&mut &nested[..]).is_ok());
+
+    // a raised one lets through what the defaults refuse
+    let count = DecodeLimits::default().max_collection_length + 1;
+    let bytes = vec![0u8; count];
+    assert!(u8::decode_many_buf(&mut &bytes[..], count).is_err());
+    let mut limits = DecodeLimits::new();
+    limits.max_collection_length = count;
+    let items = u8::decode_many_buf_limited(&mut &bytes[..], count, &mut limits).unwrap();
+    assert_eq!(items.len(), count);
+    assert_eq!(limits.allocated(), count);
+}
@@ -0,0 +1,64 @@
+mod common;
+
+use common::encoded;
+use minecraft_data_types::common::Identifier;
+use minecraft_data_types::encoder::{Decodable, Encodable};
+use minecraft_data_types::nums::VarInt;
+use minecraft_data_types::optional::{BoolPrefixed, IdOrInline, Trailing};
+use minecraft_data_types::packet::RawPacket;
+
+#[test]
+fn bool_prefixed() {
+    assert_eq!(encoded(&BoolPrefixed::<i16>(None)), [0x00]);
+    assert_eq!(encoded(&BoolPrefixed(Some(5i16))), [0x01, 0x00, 0x05]);
+    assert_eq!(
+        BoolPrefixed::<i16>::decode(&mut &[0x00, 0x00, 0x05][..]).unwrap(),
+        BoolPrefixed(None)
+    );
+    assert_eq!(
+        *BoolPrefixed::<i16>::decode_buf(&mut &[0x01, 0x00, 0x05][..]).unwrap(),
+        Some(5)
+    );
+    assert!(BoolPrefixed::<i16>::decode(&mut &[0x02][..]).is_err());
+}
+
+#[test]
+fn trailing() {
+    let present = RawPacket::new(0x01, vec![0x03, b'a', b'b', b'c']);
+    let absent = RawPacket::new(0x01, vec![]);
+    assert_eq!(
+        *present.decode_packet::<Trailing<Identifier>>().unwrap(),
+        Some(Identifier::from("abc"))
+    );
+    assert_eq!(
+        *absent.decode_packet::<Trailing<Identifier>>().unwrap(),
+        None
+    );
+    assert_eq!(
+        *Trailing::<VarInt>::decode_buf(&mut &[0xac, 0x02][..]).unwrap(),
+        Some(VarInt::from(300))
+    );
+    assert!(Trailing::<i32>::decode(&mut &[0x00, 0x01][..]).is_err());
+    assert!(encoded(&Trailing::<i32>(None)).is_empty());
+}
+
+#[test]
+fn id_or_inline() {
+    let id = IdOrInline::<Identifier>::Id(4);
+    let inline = IdOrInline::Inline(Identifier::from("a"));
+    assert_eq!(encoded(&id), [0x05]);
+    assert_eq!(encoded(&inline), [0x00, 0x01, b'a']);
+    assert_eq!(IdOrInline::decode(&mut &[0x05][..]).unwrap(), id);
+    assert_eq!(
+        IdOrInline::decode_buf(&mut &[0x00, 0x01, b'a'][..]).unwrap(),
+        inline
+    );
+    assert_eq!(id.id(), Some(4));
+    assert_eq!(inline.inline(), Some(&Identifier::from("a")));
+
+    assert!(IdOrInline::<i32>::decode(&mut &[0xff, 0xff, 0xff, 0xff, 0x0f][..]).is_err());
+    assert!(IdOrInline::<i32>::Id(-1).encode(&mut Vec::new()).is_err());
+    assert!(IdOrInline::<i32>::Id(i32::MAX)
+        .encode(&mut Vec::new())
+        .is_err());
+}
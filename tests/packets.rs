@@ -0,0 +1,81 @@
+mod common;
+
+use common::round_trip;
+use minecraft_data_types::common::Chat;
+use minecraft_data_types::encoder::Encodable;
+use minecraft_data_types::nums::VarInt;
+use minecraft_data_types::packets::handshake::{Handshake, NextState};
+use minecraft_data_types::packets::status::{
+    Favicon, PingRequest, ServerStatus, StatusJson, StatusResponse,
+};
+use minecraft_data_types::strings::McString;
+
+#[test]
+fn handshake_round_trip() {
+    let handshake = Handshake {
+        protocol_version: VarInt::from(767),
+        server_address: "play.example.com".into(),
+        server_port: 25565,
+        next_state: NextState::Status,
+    };
+    round_trip(&handshake);
+
+    let mut bytes = Vec::new();
+    handshake.encode(&mut bytes).unwrap();
+    assert_eq!(&bytes[..3], &[0xFF, 0x05, 16]);
+    assert_eq!(&bytes[bytes.len() - 3..], &[0x63, 0xDD, 0x01]);
+}
+
+#[test]
+fn ping_round_trip() {
+    let ping = PingRequest { payload: -42 };
+    round_trip(&ping);
+}
+
+#[test]
+fn server_status_json() {
+    let json = r#"{
+        "version": {"name": "1.21", "protocol": 767},
+        "players": {
+            "max": 100,
+            "online": 5,
+            "sample": [{"name": "thinkofdeath", "id": "4566e69f-c907-48ee-8d71-d7ba5aa00d20"}]
+        },
+        "description": {"text": "Hello, world!"},
+        "favicon": "data:image/png;base64,iVBORw0KGgo=",
+        "enforcesSecureChat": true
+    }"#;
+    let status = ServerStatus::from_json(&StatusJson::from(json)).unwrap();
+    assert_eq!(status.version.protocol, 767);
+    let players = status.players.as_ref().unwrap();
+    assert_eq!(players.sample[0].name, "thinkofdeath");
+    assert_eq!(
+        players.sample[0].id.to_string(),
+        "4566e69f-c907-48ee-8d71-d7ba5aa00d20"
+    );
+    assert_eq!(status.description.string(), r#"{"text":"Hello, world!"}"#);
+    assert_eq!(
+        status.favicon.as_ref().unwrap().png().unwrap(),
+        b"\x89PNG\r\n\x1a\n"
+    );
+    assert_eq!(status.enforces_secure_chat, Some(true));
+
+    let response = StatusResponse { status };
+    round_trip(&response);
+}
+
+#[test]
+fn plain_text_description() {
+    let json =
+        r#"{"version": {"name": "1.8.9", "protocol": 47}, "description": "A Minecraft Server"}"#;
+    let status = ServerStatus::from_json(&StatusJson::from(json)).unwrap();
+    assert_eq!(status.description, Chat::from(r#""A Minecraft Server""#));
+    assert!(status.players.is_none());
+    assert!(status
+        .to_json()
+        .unwrap()
+        .contains(r#""description":"A Minecraft Server""#));
+
+    let favicon = Favicon::from_png(&[1, 2, 3]);
+    assert_eq!(favicon.png().unwrap(), vec![1, 2, 3]);
+}
@@ -0,0 +1,118 @@
+use minecraft_data_types::common::Chat;
+use minecraft_data_types::encoder::Encodable;
+use minecraft_data_types::packet::RawPacket;
+use minecraft_data_types::packets::handshake::{Handshake, NextState};
+use minecraft_data_types::packets::status::{ServerStatus, StatusPlayers, StatusVersion};
+use minecraft_data_types::ping::{ping, ping_with, PingOptions, StatusResponder};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+fn status() -> ServerStatus {
+    ServerStatus {
+        version: StatusVersion {
+            name: "1.21".to_string(),
+            protocol: 767,
+        },
+        players: Some(StatusPlayers {
+            max: 20,
+            online: 3,
+            sample: vec![],
+        }),
+        description: Chat::from(r#"{"text":"mock"}"#),
+        favicon: None,
+        enforces_secure_chat: Some(false),
+    }
+}
+
+#[tokio::test]
+async fn ping_mock_server() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let responder = StatusResponder::new(status());
+    let server = tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        responder.serve(&mut stream).await.unwrap()
+    });
+
+    let result = ping(addr).await.unwrap();
+    assert_eq!(result.status, status());
+    assert!(result.latency < Duration::from_secs(5));
+    assert!(server.await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn login_handshake_is_handed_back() {
+    let (mut client, mut server) = tokio::io::duplex(1024);
+    let responder = StatusResponder::new(status());
+    let server = tokio::spawn(async move {
+        let handshake = responder.serve(&mut server).await;
+        (handshake, server)
+    });
+
+    let handshake = Handshake {
+        protocol_version: 767.into(),
+        server_address: "localhost".into(),
+        server_port: 25565,
+        next_state: NextState::Login,
+    };
+    let mut login = Vec::new();
+    RawPacket::from_packet(Handshake::ID, &handshake)
+        .unwrap()
+        .encode(&mut login)
+        .unwrap();
+    client.write_all(&login).await.unwrap();
+
+    let (handshake, server) = server.await.unwrap();
+    assert_eq!(handshake.unwrap().unwrap().next_state, NextState::Login);
+
+    // the responder must not have answered a login, so closing its end leaves nothing to read
+    drop(server);
+    let mut written = Vec::new();
+    client.read_to_end(&mut written).await.unwrap();
+    assert!(written.is_empty());
+}
+
+#[tokio::test]
+async fn ping_times_out() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let _server = tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        tokio::time::sleep(Duration::from_secs(5)).await;
+        drop(stream);
+    });
+
+    let options = PingOptions {
+        read_timeout: Duration::from_millis(100),
+        ..PingOptions::default()
+    };
+    let error = ping_with(addr, &options).await.unwrap_err();
+    assert!(error.to_string().contains("Timed out"));
+}
+
+/// Runs the status exchange with a client which sends `after` once it has the response, then
+/// stops writing.
+async fn respond_then(after: &[u8]) -> anyhow::Result<()> {
+    let (mut client, mut server) = tokio::io::duplex(4096);
+    let responder = StatusResponder::new(status());
+    let server = tokio::spawn(async move { responder.respond(&mut server).await });
+
+    client.write_all(&[0x01, 0x00]).await.unwrap();
+    client.write_all(after).await.unwrap();
+    client.shutdown().await.unwrap();
+    server.await.unwrap()
+}
+
+#[tokio::test]
+async fn status_client_may_skip_the_ping() {
+    respond_then(&[]).await.unwrap();
+}
+
+#[tokio::test]
+async fn garbage_ping_is_an_error() {
+    // a VarInt length which never ends
+    assert!(respond_then(&[0xff; 6]).await.is_err());
+    // a frame cut short
+    assert!(respond_then(&[0x09, 0x01, 0x00]).await.is_err());
+}
@@ -0,0 +1,285 @@
+#![cfg(feature = "region")]
+
+use minecraft_data_types::encoder::Decodable;
+use minecraft_data_types::limits::{DecodeLimits, LimitError};
+use minecraft_data_types::region::{Compression, Region};
+use nbt::{Blob, Value};
+use std::convert::TryInto;
+use std::path::PathBuf;
+
+fn temp_directory(name: &str) -> PathBuf {
+    let directory = std::env::temp_dir().join(format!("region-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&directory);
+    std::fs::create_dir_all(&directory).unwrap();
+    directory
+}
+
+fn chunk(x: i32, z: i32) -> Blob {
+    let mut blob = Blob::new();
+    blob.insert("xPos", x).unwrap();
+    blob.insert("zPos", z).unwrap();
+    blob.insert("Status", "minecraft:full").unwrap();
+    blob.insert("Heightmap", vec![64i64; 37]).unwrap();
+    blob
+}
+
+/// Pseudo-random bytes that don't compress.
+fn noise(length: usize) -> Vec<i8> {
+    let mut state = 0x2545_f491u32;
+    (0..length)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state as i8
+        })
+        .collect()
+}
+
+/// A region holding chunks 0, 0 and 1, 0 in lz4-java's block stream, compressed with
+/// `LZ4_compress_default` from the reference liblz4 as lz4-java's native compressor does.
+///
+/// Chunk 0, 0 doesn't shrink so is a single raw block, chunk 1, 0 is three compressed blocks.
+const LZ4_JAVA_REGION: &[u8] = include_bytes!("fixtures/lz4-java.mca");
+
+fn lz4_java_chunks() -> [Blob; 2] {
+    let mut small = Blob::new();
+    small.insert("DataVersion", 3839).unwrap();
+    small.insert("xPos", 0).unwrap();
+    small.insert("zPos", 0).unwrap();
+    small.insert("Status", "minecraft:full").unwrap();
+    small.insert("Noise", noise(300)).unwrap();
+
+    let mut large = Blob::new();
+    large.insert("DataVersion", 3839).unwrap();
+    large.insert("xPos", 1).unwrap();
+    large.insert("zPos", 0).unwrap();
+    large.insert("Status", "minecraft:full").unwrap();
+    let states = (0..20_000i64)
+        .map(|index| (index % 97) * 0x0101_0101_0101)
+        .collect();
+    large
+        .insert("BlockStates", Value::LongArray(states))
+        .unwrap();
+    [small, large]
+}
+
+/// The LZ4 stream of a chunk stored in the region, split into its block headers and bodies.
+fn lz4_blocks(region: &[u8], index: usize) -> Vec<([u8; 21], Vec<u8>)> {
+    let location = u32::from_be_bytes(region[index * 4..index * 4 + 4].try_into().unwrap());
+    let start = (location >> 8) as usize * 4096;
+    let length = u32::from_be_bytes(region[start..start + 4].try_into().unwrap()) as usize;
+    assert_eq!(region[start + 4], 4);
+    let mut stream = &region[start + 5..start + 4 + length];
+    let mut blocks = Vec::new();
+    while !stream.is_empty() {
+        let header: [u8; 21] = stream[..21].try_into().unwrap();
+        let compressed = u32::from_le_bytes(header[9..13].try_into().unwrap()) as usize;
+        blocks.push((header, stream[21..21 + compressed].to_vec()));
+        stream = &stream[21 + compressed..];
+    }
+    blocks
+}
+
+#[test]
+fn reads_lz4_java_chunks() {
+    let directory = temp_directory("lz4-java");
+    let path = directory.join("r.0.0.mca");
+    std::fs::write(&path, LZ4_JAVA_REGION).unwrap();
+    let mut region = Region::open(&path).unwrap();
+    let [small, large] = lz4_java_chunks();
+    assert_eq!(region.read_chunk(0, 0).unwrap(), Some(small));
+    assert_eq!(region.read_chunk(1, 0).unwrap(), Some(large));
+    assert_eq!(region.timestamp(1, 0).unwrap(), 1_717_000_000);
+
+    // the decompressed chunk is held to the NBT limit
+    let mut limits = DecodeLimits::new();
+    limits.max_nbt_bytes = 100_000;
+    let mut region = Region::open(&path).unwrap().with_limits(limits);
+    assert!(region.read_chunk(1, 0).is_err());
+
+    // a flipped byte in a compressed block fails its checksum or the block itself
+    let mut corrupt = LZ4_JAVA_REGION.to_vec();
+    corrupt[3 * 4096 + 5 + 21 + 100] ^= 0x40;
+    std::fs::write(&path, &corrupt).unwrap();
+    let mut region = Region::open(&path).unwrap();
+    assert!(region.read_chunk(1, 0).is_err());
+
+    // a stream cut off before its empty end block is truncated, as lz4-java has it
+    let mut truncated = LZ4_JAVA_REGION.to_vec();
+    let start = u32::from_be_bytes([0, truncated[0], truncated[1], truncated[2]]) as usize * 4096;
+    let length = u32::from_be_bytes(truncated[start..start + 4].try_into().unwrap());
+    truncated[start..start + 4].copy_from_slice(&(length - 21).to_be_bytes());
+    std::fs::write(&path, &truncated).unwrap();
+    let mut region = Region::open(&path).unwrap();
+    assert!(region.read_chunk(0, 0).is_err());
+    std::fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn writes_lz4_java_chunks() {
+    let directory = temp_directory("lz4-write");
+    let path = directory.join("r.0.0.mca");
+    {
+        let mut region = Region::open(&path).unwrap();
+        let [small, large] = lz4_java_chunks();
+        region.write_chunk(0, 0, &small, Compression::Lz4).unwrap();
+        region.write_chunk(1, 0, &large, Compression::Lz4).unwrap();
+    }
+    let written = std::fs::read(&path).unwrap();
+
+    // hematite's compounds are unordered, so the NBT bytes rather than the stream are compared
+    for (index, chunk) in lz4_java_chunks().iter().enumerate() {
+        let expected = lz4_blocks(LZ4_JAVA_REGION, index);
+        let blocks = lz4_blocks(&written, index);
+        assert_eq!(blocks.len(), expected.len());
+        let mut nbt = Vec::new();
+        for ((header, block), (expected_header, _)) in blocks.iter().zip(&expected) {
+            // whether a small block shrinks depends on the order, so only the level must match
+            assert_eq!(header[..8], expected_header[..8]);
+            assert_eq!(header[8] & 0x0f, expected_header[8] & 0x0f);
+            assert_eq!(header[13..17], expected_header[13..17]);
+            let original = u32::from_le_bytes(header[13..17].try_into().unwrap()) as usize;
+            let bytes = match header[8] & 0xf0 {
+                0x20 => lz4::block::decompress(block, Some(original as i32)).unwrap(),
+                _ => block.clone(),
+            };
+            assert_eq!(bytes.len(), original);
+            let checksum = u32::from_le_bytes(header[17..].try_into().unwrap());
+            if original > 0 {
+                assert_eq!(
+                    checksum,
+                    twox_hash::XxHash32::oneshot(0x9747_b28c, &bytes) & 0x0fff_ffff
+                );
+            } else {
+                assert_eq!(checksum, 0);
+            }
+            nbt.extend_from_slice(&bytes);
+        }
+        assert_eq!(&Blob::decode(&mut &nbt[..]).unwrap(), chunk);
+    }
+    std::fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn chunks_round_trip_through_every_compression() {
+    let directory = temp_directory("compression");
+    let path = directory.join("r.-1.2.mca");
+    {
+        let mut region = Region::open(&path).unwrap();
+        assert_eq!((region.x(), region.z()), (-1, 2));
+        let compressions = [
+            Compression::Gzip,
+            Compression::Zlib,
+            Compression::None,
+            Compression::Lz4,
+        ];
+        for (index, compression) in compressions.iter().enumerate() {
+            let x = -32 + index as i32;
+            region
+                .write_chunk(x, 95, &chunk(x, 95), *compression)
+                .unwrap();
+        }
+        assert!(region
+            .write_chunk(0, 95, &chunk(0, 95), Compression::Zlib)
+            .is_err());
+        assert!(region.read_chunk(-1, 64).unwrap().is_none());
+    }
+    assert_eq!(std::fs::metadata(&path).unwrap().len(), 6 * 4096);
+
+    let mut region = Region::open(&path).unwrap();
+    for x in -32..-28 {
+        assert_eq!(region.read_chunk(x, 95).unwrap(), Some(chunk(x, 95)));
+        assert!(region.timestamp(x, 95).unwrap() > 0);
+    }
+    assert!(!region.contains(-27, 95).unwrap());
+    std::fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn sectors_are_reused_and_overflow_goes_external() {
+    let directory = temp_directory("sectors");
+    let path = directory.join("r.0.0.mca");
+    let mut region = Region::open(&path).unwrap();
+
+    let mut big = chunk(1, 1);
+    big.insert("Noise", noise(20_000)).unwrap();
+    region
+        .write_chunk(0, 0, &chunk(0, 0), Compression::None)
+        .unwrap();
+    region.write_chunk(1, 1, &big, Compression::None).unwrap();
+    region
+        .write_chunk(2, 2, &chunk(2, 2), Compression::None)
+        .unwrap();
+    assert_eq!(std::fs::metadata(&path).unwrap().len(), 9 * 4096);
+
+    // the freed sectors of the big chunk take the next ones
+    region
+        .write_chunk(1, 1, &chunk(1, 1), Compression::None)
+        .unwrap();
+    region
+        .write_chunk(3, 3, &chunk(3, 3), Compression::None)
+        .unwrap();
+    region.remove_chunk(2, 2).unwrap();
+    region
+        .write_chunk(4, 4, &chunk(4, 4), Compression::None)
+        .unwrap();
+    assert_eq!(std::fs::metadata(&path).unwrap().len(), 9 * 4096);
+    assert!(region.read_chunk(2, 2).unwrap().is_none());
+
+    // more than 255 sectors
+    let mut huge = chunk(5, 5);
+    huge.insert("Noise", noise(1_100_000)).unwrap();
+    region.write_chunk(5, 5, &huge, Compression::None).unwrap();
+    let external = directory.join("c.5.5.mcc");
+    assert!(external.exists());
+    drop(region);
+
+    let mut region = Region::open(&path).unwrap();
+    assert_eq!(region.read_chunk(5, 5).unwrap(), Some(huge));
+    // an external chunk is held to the allocation budget before it is read
+    let mut limits = DecodeLimits::new();
+    limits.max_packet_allocation = 1_000_000;
+    let error = Region::open(&path)
+        .unwrap()
+        .with_limits(limits)
+        .read_chunk(5, 5)
+        .unwrap_err();
+    assert_eq!(
+        error.downcast_ref::<LimitError>(),
+        Some(&LimitError::PacketAllocation {
+            requested: std::fs::metadata(&external).unwrap().len() as usize,
+            remaining: 1_000_000,
+        })
+    );
+    for position in [0, 1, 3, 4] {
+        assert_eq!(
+            region.read_chunk(position, position).unwrap(),
+            Some(chunk(position, position))
+        );
+    }
+    region
+        .write_chunk(5, 5, &chunk(5, 5), Compression::Lz4)
+        .unwrap();
+    assert!(!external.exists());
+    assert_eq!(region.read_chunk(5, 5).unwrap(), Some(chunk(5, 5)));
+    std::fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn rejects_bad_regions() {
+    let directory = temp_directory("bad");
+    assert!(Region::open(directory.join("region.mca")).is_err());
+
+    let path = directory.join("r.0.0.mca");
+    let mut header = vec![0u8; 8192];
+    // chunk 0, 0 claims to be in the header
+    header[..4].copy_from_slice(&[0, 0, 1, 1]);
+    // chunk 1, 0 is past the end of the file
+    header[4..8].copy_from_slice(&[0, 0, 9, 1]);
+    std::fs::write(&path, &header).unwrap();
+    let mut region = Region::open(&path).unwrap();
+    assert!(region.read_chunk(0, 0).is_err());
+    assert!(region.read_chunk(1, 0).is_err());
+    std::fs::remove_dir_all(&directory).unwrap();
+}
@@ -0,0 +1,101 @@
+use minecraft_data_types::encoder::{Decodable, Encodable};
+use minecraft_data_types::nums::VarInt;
+use minecraft_data_types::packet::RawPacket;
+use minecraft_data_types::packet_enum;
+use minecraft_data_types::registry::{DecodedPacket, PacketRegistry, ALL_PROTOCOLS};
+use minecraft_data_types::state::{ConnectionState, Direction};
+use std::any::Any;
+use std::io::{Read, Write};
+
+#[derive(Debug, PartialEq)]
+struct KeepAlive(i64);
+
+impl Decodable for KeepAlive {
+    fn decode<R: Read>(reader: &mut R) -> anyhow::Result<Self> {
+        Ok(KeepAlive(i64::decode(reader)?))
+    }
+}
+
+impl Encodable for KeepAlive {
+    fn encode<W: Write>(&self, writer: &mut W) -> anyhow::Result<()> {
+        self.0.encode(writer)
+    }
+
+    fn size(&self) -> anyhow::Result<VarInt> {
+        self.0.size()
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct Ping(i32);
+
+impl Decodable for Ping {
+    fn decode<R: Read>(reader: &mut R) -> anyhow::Result<Self> {
+        Ok(Ping(i32::decode(reader)?))
+    }
+}
+
+packet_enum! {
+    #[derive(Debug, PartialEq)]
+    enum PlayPacket {
+        KeepAlive(KeepAlive),
+        Ping(Ping),
+    }
+}
+
+const PLAY: ConnectionState = ConnectionState::Play;
+const CLIENTBOUND: Direction = Direction::Clientbound;
+
+#[test]
+fn decodes_into_enum_per_protocol() {
+    let mut registry = PacketRegistry::<PlayPacket>::new();
+    registry.register::<KeepAlive>(PLAY, CLIENTBOUND, vec![(0..=765, 0x24), (766..=767, 0x26)]);
+    registry.register::<Ping>(PLAY, CLIENTBOUND, vec![(ALL_PROTOCOLS, 0x33)]);
+
+    let packet = RawPacket::new(0x26, 7i64.to_be_bytes().to_vec());
+    match registry
+        .decode(767, PLAY, CLIENTBOUND, packet.clone())
+        .unwrap()
+    {
+        DecodedPacket::Known(decoded) => assert_eq!(decoded, PlayPacket::KeepAlive(KeepAlive(7))),
+        DecodedPacket::Unknown(_) => panic!("Expected keep alive."),
+    }
+    match registry
+        .decode(765, PLAY, CLIENTBOUND, packet.clone())
+        .unwrap()
+    {
+        DecodedPacket::Unknown(raw) => assert_eq!(raw, packet),
+        DecodedPacket::Known(_) => panic!("Expected unknown packet."),
+    }
+
+    assert_eq!(
+        registry.id_of::<KeepAlive>(700, PLAY, CLIENTBOUND),
+        Some(0x24)
+    );
+    assert_eq!(
+        registry
+            .encode(767, PLAY, CLIENTBOUND, &KeepAlive(7))
+            .unwrap(),
+        packet
+    );
+    assert!(registry
+        .encode(767, PLAY, Direction::Serverbound, &KeepAlive(7))
+        .is_err());
+}
+
+#[test]
+fn decodes_into_any() {
+    let mut registry = PacketRegistry::<Box<dyn Any + Send>>::new();
+    registry.register_any::<Ping>(PLAY, CLIENTBOUND, vec![(ALL_PROTOCOLS, 0x33)]);
+
+    let packet = RawPacket::new(0x33, 5i32.to_be_bytes().to_vec());
+    match registry.decode(1, PLAY, CLIENTBOUND, packet).unwrap() {
+        DecodedPacket::Known(decoded) => {
+            assert_eq!(decoded.downcast_ref::<Ping>(), Some(&Ping(5)))
+        }
+        DecodedPacket::Unknown(_) => panic!("Expected ping."),
+    }
+
+    let trailing = RawPacket::new(0x33, vec![0, 0, 0, 5, 1]);
+    assert!(registry.decode(1, PLAY, CLIENTBOUND, trailing).is_err());
+}
@@ -0,0 +1,245 @@
+mod common;
+
+use common::{encoded, round_trip};
+use minecraft_data_types::collections::{ByteArray, Fixed, Prefixed, PrefixedBytes, Remaining};
+use minecraft_data_types::common::{Chat, Identifier};
+use minecraft_data_types::encoder::{Decodable, Encodable};
+use minecraft_data_types::nums::{Le, VarInt, VarIntZigZag, VarLong, VarLongZigZag};
+use minecraft_data_types::optional::{BoolPrefixed, IdOrInline};
+use nbt::Blob;
+use proptest::prelude::*;
+use uuid::Uuid;
+
+fn var_int() -> impl Strategy<Value = VarInt> {
+    any::<i32>().prop_map(VarInt::from)
+}
+
+fn identifier() -> impl Strategy<Value = Identifier> {
+    ".{0,64}".prop_map(Identifier::from)
+}
+
+fn blob() -> impl Strategy<Value = Blob> {
+    let entry = prop_oneof![
+        any::<i32>().prop_map(nbt::Value::Int),
+        any::<i64>().prop_map(nbt::Value::Long),
+        ".{0,16}".prop_map(nbt::Value::String),
+        prop::collection::vec(any::<i64>(), 0..8).prop_map(nbt::Value::LongArray),
+    ];
+    prop::collection::hash_map("[a-zA-Z_]{1,12}", entry, 0..8).prop_map(|entries| {
+        let mut blob = Blob::new();
+        for (name, value) in entries {
+            blob.insert(name, value).unwrap();
+        }
+        blob
+    })
+}
+
+macro_rules! primitive_round_trips {
+    ($($name:ident: $primitive:ty),*) => {
+        proptest! {
+            $(
+                #[test]
+                fn $name(value in any::<$primitive>()) {
+                    round_trip(&value);
+                    prop_assert_eq!(encoded(&value), value.to_be_bytes().to_vec());
+                }
+            )*
+        }
+    };
+}
+
+primitive_round_trips!(
+    i8_round_trip: i8,
+    u8_round_trip: u8,
+    i16_round_trip: i16,
+    u16_round_trip: u16,
+    i32_round_trip: i32,
+    u32_round_trip: u32,
+    i64_round_trip: i64,
+    u64_round_trip: u64,
+    i128_round_trip: i128,
+    u128_round_trip: u128
+);
+
+proptest! {
+    #[test]
+    fn little_endian_round_trip(int in any::<i32>(), long in any::<u64>(), wide in any::<u128>()) {
+        round_trip(&Le(int));
+        round_trip(&Le(long));
+        round_trip(&Le(wide));
+        prop_assert_eq!(encoded(&Le(long)), long.to_le_bytes().to_vec());
+    }
+}
+
+proptest! {
+    // floats compare by bits so NaN payloads count too
+    #[test]
+    fn f32_round_trip(bits in any::<u32>()) {
+        let bytes = encoded(&f32::from_bits(bits));
+        prop_assert_eq!(&bytes, &bits.to_be_bytes());
+        prop_assert_eq!(f32::decode(&mut &bytes[..]).unwrap().to_bits(), bits);
+    }
+
+    #[test]
+    fn f64_round_trip(bits in any::<u64>()) {
+        let bytes = encoded(&f64::from_bits(bits));
+        prop_assert_eq!(&bytes, &bits.to_be_bytes());
+        prop_assert_eq!(f64::decode(&mut &bytes[..]).unwrap().to_bits(), bits);
+    }
+
+    #[test]
+    fn bool_round_trip(value in any::<bool>()) {
+        round_trip(&value);
+    }
+
+    #[test]
+    fn var_int_round_trip(value in var_int()) {
+        round_trip(&value);
+    }
+
+    #[test]
+    fn zig_zag_round_trip(int in any::<i32>(), long in any::<i64>()) {
+        round_trip(&VarIntZigZag::from(int));
+        round_trip(&VarLongZigZag::from(long));
+    }
+
+    #[test]
+    fn var_long_round_trip(value in any::<i64>().prop_map(VarLong::from)) {
+        round_trip(&value);
+    }
+
+    #[test]
+    fn identifier_round_trip(value in identifier()) {
+        round_trip(&value);
+    }
+
+    #[test]
+    fn chat_round_trip(text in ".{0,512}") {
+        round_trip(&Chat::from(format!(r#"{{"text":{:?}}}"#, text)));
+    }
+
+    #[test]
+    fn uuid_round_trip(bytes in any::<[u8; 16]>()) {
+        round_trip(&Uuid::from_bytes(bytes));
+    }
+
+    #[test]
+    fn blob_round_trip(value in blob()) {
+        round_trip(&value);
+    }
+
+    #[test]
+    fn remaining_round_trip(value in prop::collection::vec(var_int(), 0..64)) {
+        round_trip(&Remaining(value));
+    }
+
+    #[test]
+    fn prefixed_round_trip(value in prop::collection::vec(any::<i64>(), 0..64)) {
+        round_trip(&Prefixed::<VarInt, _>::new(value.clone()));
+        round_trip(&Prefixed::<u8, _>::new(value.clone()));
+        round_trip(&Prefixed::<i16, _>::new(value.clone()));
+        round_trip(&Prefixed::<i32, _>::new(value));
+    }
+
+    #[test]
+    fn array_round_trip(bytes in any::<[u8; 16]>(), longs in any::<[i64; 4]>()) {
+        round_trip(&bytes);
+        round_trip(&longs);
+    }
+
+    #[test]
+    fn byte_array_round_trip(bytes in prop::collection::vec(any::<u8>(), 0..512)) {
+        round_trip(&PrefixedBytes::<VarInt>::new(bytes.clone()));
+        round_trip(&PrefixedBytes::<u8>::new(bytes[..bytes.len().min(255)].to_vec()));
+        round_trip(&ByteArray(bytes));
+    }
+
+    #[test]
+    fn fixed_round_trip(value in prop::collection::vec(identifier(), 3)) {
+        round_trip(&Fixed::<3, _>(value));
+    }
+
+    #[test]
+    fn bool_prefixed_round_trip(value in proptest::option::of(identifier())) {
+        round_trip(&BoolPrefixed(value));
+    }
+
+    #[test]
+    fn id_or_inline_round_trip(id in 0..i32::MAX, inline in identifier(), is_id in any::<bool>()) {
+        let value = if is_id { IdOrInline::Id(id) } else { IdOrInline::Inline(inline) };
+        round_trip(&value);
+    }
+
+    #[test]
+    fn pair_round_trip(value in (var_int(), prop::collection::vec(identifier(), 0..4))) {
+        round_trip(&(value.0, Prefixed::<VarInt, _>::new(value.1)));
+    }
+
+    #[test]
+    fn triple_round_trip(value in (var_int(), identifier(), any::<u16>())) {
+        round_trip(&value);
+    }
+
+    #[test]
+    fn twelve_round_trip(
+        first in (any::<bool>(), any::<i8>(), any::<u8>(), any::<i16>(), any::<u16>(), any::<i32>()),
+        second in (any::<i64>(), var_int(), identifier(), any::<[u8; 16]>(), any::<bool>(), any::<u8>()),
+    ) {
+        let (a, b, c, d, e, f) = first;
+        let (g, h, i, j, k, l) = second;
+        round_trip(&(a, b, c, d, e, f, g, h, i, Uuid::from_bytes(j), k, (l,)));
+    }
+}
+
+#[test]
+fn string_limits() {
+    let longest = Identifier::from("a".repeat(32767));
+    assert_eq!(encoded(&longest)[..3], [0xff, 0xff, 0x01]);
+    assert_eq!(*longest.size().unwrap(), 32767 + 3);
+    assert!(Identifier::from("a".repeat(32768))
+        .encode(&mut Vec::new())
+        .is_err());
+}
+
+/// Known answers from the wiki.vg protocol data type table.
+#[test]
+fn known_answers() {
+    assert_eq!(encoded(&true), [0x01]);
+    assert_eq!(encoded(&false), [0x00]);
+    assert_eq!(encoded(&-2i8), [0xfe]);
+    assert_eq!(encoded(&25565u16), [0x63, 0xdd]);
+    assert_eq!(encoded(&-2i32), [0xff, 0xff, 0xff, 0xfe]);
+    assert_eq!(encoded(&1.0f32), [0x3f, 0x80, 0x00, 0x00]);
+    assert_eq!(encoded(&Le(25565u16)), [0xdd, 0x63]);
+    assert_eq!(encoded(&Le(1.0f32)), [0x00, 0x00, 0x80, 0x3f]);
+    assert_eq!(encoded(&u64::MAX), [0xff; 8]);
+    assert_eq!(encoded(&1u128)[15], 0x01);
+    assert_eq!(
+        encoded(&-2.5f64),
+        [0xc0, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]
+    );
+    assert_eq!(encoded(&VarInt::from(-1)), [0xff, 0xff, 0xff, 0xff, 0x0f]);
+    assert_eq!(encoded(&VarInt::from(25565)), [0xdd, 0xc7, 0x01]);
+    assert_eq!(
+        encoded(&VarLong::from(-2147483648)),
+        [0x80, 0x80, 0x80, 0x80, 0xf8, 0xff, 0xff, 0xff, 0xff, 0x01]
+    );
+    assert_eq!(
+        encoded(&Identifier::from("minecraft:stone")),
+        b"\x0fminecraft:stone"
+    );
+    assert_eq!(
+        encoded(&Uuid::parse_str("4566e69f-c907-48ee-8d71-d7ba5aa00d20").unwrap()),
+        [
+            0x45, 0x66, 0xe6, 0x9f, 0xc9, 0x07, 0x48, 0xee, 0x8d, 0x71, 0xd7, 0xba, 0x5a, 0xa0,
+            0x0d, 0x20
+        ]
+    );
+
+    let mut blob = Blob::new();
+    blob.insert("a", 1i8).unwrap();
+    assert_eq!(
+        encoded(&blob),
+        [0x0a, 0x00, 0x00, 0x01, 0x00, 0x01, b'a', 0x01, 0x00]
+    );
+}
@@ -1,4 +1,5 @@
 #[test]
+#[allow(clippy::assertions_on_constants)]
 fn sanity() {
     assert!(true);
     assert_eq!(false, false);
@@ -0,0 +1,173 @@
+use minecraft_data_types::encoder::Encodable;
+use minecraft_data_types::nums::VarInt;
+use minecraft_data_types::packet::RawPacket;
+use minecraft_data_types::state::{
+    ConnectionReader, ConnectionState, ConnectionStateMachine, Direction, TransitionIds,
+};
+
+fn handshake(protocol_version: i32, next_state: i32) -> RawPacket {
+    let mut data = Vec::new();
+    VarInt::from(protocol_version).encode(&mut data).unwrap();
+    VarInt::from(9).encode(&mut data).unwrap();
+    data.extend_from_slice(b"localhost");
+    25565u16.encode(&mut data).unwrap();
+    VarInt::from(next_state).encode(&mut data).unwrap();
+    RawPacket::new(0x00, data)
+}
+
+#[test]
+fn raw_packet_round_trip() {
+    let packet = RawPacket::new(0x2A, vec![1, 2, 3]);
+    let mut bytes = Vec::new();
+    packet.encode(&mut bytes).unwrap();
+    assert_eq!(bytes, vec![4, 0x2A, 1, 2, 3]);
+    assert_eq!(packet.size().unwrap(), bytes.len() as i32);
+
+    let mut reader = ConnectionReader::with_state_machine(
+        std::io::Cursor::new(bytes),
+        Direction::Clientbound,
+        ConnectionStateMachine::with_transitions(TransitionIds::for_protocol(767).unwrap()),
+    );
+    reader
+        .state_machine_mut()
+        .set_state(Direction::Clientbound, ConnectionState::Play);
+    assert_eq!(
+        reader.read_packet().unwrap(),
+        (ConnectionState::Play, packet)
+    );
+}
+
+#[test]
+fn handshake_to_status() {
+    let mut bytes = Vec::new();
+    handshake(767, 1).encode(&mut bytes).unwrap();
+    RawPacket::new(0x00, vec![]).encode(&mut bytes).unwrap();
+
+    let mut reader = ConnectionReader::new(std::io::Cursor::new(bytes), Direction::Serverbound);
+    assert_eq!(
+        reader.next_frame(),
+        (Direction::Serverbound, ConnectionState::Handshaking)
+    );
+    assert_eq!(
+        reader.read_packet().unwrap().0,
+        ConnectionState::Handshaking
+    );
+    assert_eq!(
+        reader.next_frame(),
+        (Direction::Serverbound, ConnectionState::Status)
+    );
+    assert_eq!(reader.read_packet().unwrap().0, ConnectionState::Status);
+    assert_eq!(reader.state_machine().protocol_version(), Some(767));
+}
+
+#[test]
+fn login_through_configuration_to_play() {
+    let mut machine = ConnectionStateMachine::new();
+    machine
+        .observe(Direction::Serverbound, &handshake(767, 2))
+        .unwrap();
+    assert_eq!(
+        machine.state(Direction::Clientbound),
+        ConnectionState::Login
+    );
+
+    machine
+        .observe(Direction::Clientbound, &RawPacket::new(0x02, vec![]))
+        .unwrap();
+    assert_eq!(
+        machine.state(Direction::Clientbound),
+        ConnectionState::Configuration
+    );
+    assert_eq!(
+        machine.state(Direction::Serverbound),
+        ConnectionState::Login
+    );
+
+    machine
+        .observe(Direction::Serverbound, &RawPacket::new(0x03, vec![]))
+        .unwrap();
+    assert_eq!(
+        machine.state(Direction::Serverbound),
+        ConnectionState::Configuration
+    );
+
+    machine
+        .observe(Direction::Clientbound, &RawPacket::new(0x03, vec![]))
+        .unwrap();
+    machine
+        .observe(Direction::Serverbound, &RawPacket::new(0x03, vec![]))
+        .unwrap();
+    assert_eq!(machine.state(Direction::Clientbound), ConnectionState::Play);
+    assert_eq!(machine.state(Direction::Serverbound), ConnectionState::Play);
+
+    machine
+        .observe(Direction::Clientbound, &RawPacket::new(0x69, vec![]))
+        .unwrap();
+    machine
+        .observe(Direction::Serverbound, &RawPacket::new(0x0C, vec![]))
+        .unwrap();
+    assert_eq!(
+        machine.state(Direction::Clientbound),
+        ConnectionState::Configuration
+    );
+    assert_eq!(
+        machine.state(Direction::Serverbound),
+        ConnectionState::Configuration
+    );
+}
+
+#[test]
+fn legacy_login_skips_configuration() {
+    let mut machine = ConnectionStateMachine::new();
+    machine
+        .observe(Direction::Serverbound, &handshake(763, 2))
+        .unwrap();
+    machine
+        .observe(Direction::Clientbound, &RawPacket::new(0x02, vec![]))
+        .unwrap();
+    assert_eq!(machine.state(Direction::Clientbound), ConnectionState::Play);
+    assert_eq!(machine.state(Direction::Serverbound), ConnectionState::Play);
+}
+
+#[test]
+fn rejects_unknown_next_state() {
+    let mut machine = ConnectionStateMachine::new();
+    assert!(machine
+        .observe(Direction::Serverbound, &handshake(767, 7))
+        .is_err());
+}
+
+#[test]
+fn unsupported_protocol_is_an_error() {
+    assert!(TransitionIds::for_protocol(768).is_err());
+
+    // status works whatever the version
+    let mut machine = ConnectionStateMachine::new();
+    machine
+        .observe(Direction::Serverbound, &handshake(768, 1))
+        .unwrap();
+    machine
+        .observe(Direction::Clientbound, &RawPacket::new(0x00, vec![]))
+        .unwrap();
+
+    let mut machine = ConnectionStateMachine::new();
+    machine
+        .observe(Direction::Serverbound, &handshake(768, 2))
+        .unwrap();
+    assert!(machine
+        .observe(Direction::Clientbound, &RawPacket::new(0x02, vec![]))
+        .is_err());
+    assert_eq!(
+        machine.state(Direction::Clientbound),
+        ConnectionState::Login
+    );
+}
+
+#[test]
+fn no_handshake_is_an_error() {
+    let mut machine = ConnectionStateMachine::new();
+    machine.set_state(Direction::Clientbound, ConnectionState::Play);
+    assert!(machine
+        .observe(Direction::Clientbound, &RawPacket::new(0x69, vec![]))
+        .is_err());
+}
@@ -0,0 +1,184 @@
+use minecraft_data_types::encoder::{Decodable, Encodable};
+use minecraft_data_types::nums::{VarInt, VarIntZigZag, VarLong, VarLongZigZag};
+
+const VAR_INTS: &[(i32, &[u8])] = &[
+    (0, &[0x00]),
+    (1, &[0x01]),
+    (127, &[0x7f]),
+    (128, &[0x80, 0x01]),
+    (255, &[0xff, 0x01]),
+    (25565, &[0xdd, 0xc7, 0x01]),
+    (2097151, &[0xff, 0xff, 0x7f]),
+    (2147483647, &[0xff, 0xff, 0xff, 0xff, 0x07]),
+    (-1, &[0xff, 0xff, 0xff, 0xff, 0x0f]),
+    (-2147483648, &[0x80, 0x80, 0x80, 0x80, 0x08]),
+];
+
+const VAR_LONGS: &[(i64, &[u8])] = &[
+    (0, &[0x00]),
+    (128, &[0x80, 0x01]),
+    (2147483647, &[0xff, 0xff, 0xff, 0xff, 0x07]),
+    (
+        9223372036854775807,
+        &[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x7f],
+    ),
+    (
+        -1,
+        &[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01],
+    ),
+    (
+        -9223372036854775808,
+        &[0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x01],
+    ),
+];
+
+const ZIG_ZAG_INTS: &[(i32, &[u8])] = &[
+    (0, &[0x00]),
+    (-1, &[0x01]),
+    (1, &[0x02]),
+    (-2, &[0x03]),
+    (-64, &[0x7f]),
+    (64, &[0x80, 0x01]),
+    (2147483647, &[0xfe, 0xff, 0xff, 0xff, 0x0f]),
+    (-2147483648, &[0xff, 0xff, 0xff, 0xff, 0x0f]),
+];
+
+#[test]
+fn var_int_vectors() {
+    for (value, bytes) in VAR_INTS {
+        let var_int = VarInt::from(*value);
+        let mut encoded = Vec::new();
+        var_int.encode(&mut encoded).unwrap();
+        assert_eq!(&encoded, bytes);
+        assert_eq!(var_int.size().unwrap(), bytes.len() as i32);
+
+        // short slices take the byte loop, padded ones the word path
+        let mut padded = bytes.to_vec();
+        padded.extend_from_slice(&[0xAA; 8]);
+        for slice in [*bytes, &padded[..]] {
+            assert_eq!(VarInt::decode_slice(slice).unwrap(), (var_int, bytes.len()));
+        }
+        assert_eq!(
+            VarInt::decode(&mut std::io::Cursor::new(bytes)).unwrap(),
+            var_int
+        );
+    }
+}
+
+#[test]
+fn var_long_vectors() {
+    for (value, bytes) in VAR_LONGS {
+        let var_long = VarLong::from(*value);
+        let mut encoded = Vec::new();
+        var_long.encode(&mut encoded).unwrap();
+        assert_eq!(&encoded, bytes);
+        assert_eq!(var_long.size().unwrap(), bytes.len() as i32);
+
+        let mut padded = bytes.to_vec();
+        padded.extend_from_slice(&[0xAA; 8]);
+        for slice in [*bytes, &padded[..]] {
+            assert_eq!(
+                VarLong::decode_slice(slice).unwrap(),
+                (var_long, bytes.len())
+            );
+        }
+    }
+}
+
+#[test]
+fn decode_slice_rejects_malformed() {
+    assert!(VarInt::decode_slice(&[]).is_err());
+    assert!(VarInt::decode_slice(&[0x80, 0x80]).is_err());
+    assert!(VarInt::decode_slice(&[0x80; 6]).is_err());
+    assert!(VarInt::decode_slice(&[0x80, 0x80, 0x80, 0x80, 0x80, 0x01, 0, 0]).is_err());
+    assert!(VarInt::decode_slice(&[0x80; 8]).is_err());
+    assert!(VarLong::decode_slice(&[0x80; 11]).is_err());
+}
+
+#[test]
+fn size_matches_encoding_at_group_boundaries() {
+    for shift in 0..64 {
+        for value in [1i64 << shift, (1i64 << shift).wrapping_sub(1)] {
+            let var_long = VarLong::from(value);
+            let mut encoded = Vec::new();
+            var_long.encode(&mut encoded).unwrap();
+            assert_eq!(var_long.size().unwrap(), encoded.len() as i32);
+
+            let var_int = VarInt::from(value as i32);
+            encoded.clear();
+            var_int.encode(&mut encoded).unwrap();
+            assert_eq!(var_int.size().unwrap(), encoded.len() as i32);
+        }
+    }
+}
+
+#[test]
+fn checked_arithmetic() {
+    let max = VarInt::from(i32::MAX);
+    let one = VarInt::from(1);
+    assert_eq!(max.checked_add(one), None);
+    assert_eq!(max.saturating_add(one), max);
+    assert_eq!(max.wrapping_add(one), VarInt::from(i32::MIN));
+    assert_eq!(VarInt::from(i32::MIN).checked_sub(one), None);
+    assert_eq!(VarLong::from(i64::MAX).checked_mul(VarLong::from(2)), None);
+    assert_eq!(
+        VarLong::from(3).checked_mul(VarLong::from(2)),
+        Some(VarLong::from(6))
+    );
+    assert!(max.try_add(one).is_err());
+
+    let mut value = VarInt::from(10);
+    value -= VarInt::from(4);
+    value *= VarInt::from(3);
+    assert_eq!(value, VarInt::from(18));
+    assert_eq!(value - VarInt::from(18), VarInt::from(0));
+}
+
+/// Reports the largest size a `VarInt` can hold without writing anything.
+struct Huge;
+
+impl Encodable for Huge {
+    fn encode<W: std::io::Write>(&self, _writer: &mut W) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn size(&self) -> anyhow::Result<VarInt> {
+        Ok(VarInt::from(i32::MAX))
+    }
+
+    fn encode_buf<B: bytes::BufMut>(&self, _buf: &mut B) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn size_overflow_is_an_error() {
+    assert!((Huge, Huge).size().is_err());
+    assert!(vec![Huge, Huge].size().is_err());
+    assert!(Huge.size().is_ok());
+}
+
+#[test]
+fn zig_zag_vectors() {
+    for (value, bytes) in ZIG_ZAG_INTS {
+        let var_int = VarIntZigZag::from(*value);
+        let mut encoded = Vec::new();
+        var_int.encode(&mut encoded).unwrap();
+        assert_eq!(&encoded, bytes);
+        assert_eq!(var_int.size().unwrap(), bytes.len() as i32);
+        assert_eq!(
+            VarIntZigZag::decode_slice(bytes).unwrap(),
+            (var_int, bytes.len())
+        );
+        assert_eq!(VarIntZigZag::decode(&mut &bytes[..]).unwrap(), var_int);
+    }
+
+    let mut encoded = Vec::new();
+    VarLongZigZag::from(i64::MIN).encode(&mut encoded).unwrap();
+    assert_eq!(
+        encoded,
+        [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01]
+    );
+    assert_eq!(VarLongZigZag::decode(&mut &encoded[..]).unwrap(), i64::MIN);
+    assert_eq!(VarLongZigZag::from(-3).size().unwrap(), 1);
+}